@@ -1,5 +1,6 @@
+#![allow(clippy::to_string_in_format_args, clippy::unnecessary_unwrap)]
 use std::collections::HashMap;
-use wmclient::WmClient;
+use wmclient::{CapabilityParsing, WmClient};
 
 fn main() {
     // Let's create the WURFL microservice client by setting the connection data of out WURFL Microservice server
@@ -32,11 +33,15 @@ fn main() {
 
     // set the capabilities we want to receive from WM server
     // Static capabilities
-    let static_caps = vec! {"model_name brand_name"};
-    client.set_requested_static_capabilities(Some(static_caps));
+    let static_caps = vec! {"model_name", "brand_name"};
+    if let Err(err) = client.try_set_requested_static_capabilities(static_caps, CapabilityParsing::Strict) {
+        println!("Unable to set requested static capabilities: {}", err);
+    }
     // Virtual capabilities
-    let virtual_caps = vec! {"is_smartphone form_factor"};
-    client.set_requested_virtual_capabilities(Some(virtual_caps));
+    let virtual_caps = vec! {"is_smartphone", "form_factor"};
+    if let Err(err) = client.try_set_requested_virtual_capabilities(virtual_caps, CapabilityParsing::Strict) {
+        println!("Unable to set requested virtual capabilities: {}", err);
+    }
 
     // use this headers to perform a device detection.
     let mut headers = HashMap::new();
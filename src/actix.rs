@@ -0,0 +1,183 @@
+/// WmDetection - actix-web middleware that detects the device of every incoming request and stores it in the
+/// request extensions as a `DetectedDevice`, so that handlers can get it with the `DetectedDevice` extractor.
+/// If detection fails the request goes on without a device and the lookup error is stored instead: the extractor
+/// then rejects the request with it, without looking the headers up again.
+/// The middleware can also add the `Vary` and `Accept-CH`/`Critical-CH` headers to responses.
+/// Basic usage:
+/// ```ignore
+/// App::new()
+///     .app_data(web::Data::new(async_client.clone()))
+///     .wrap(WmDetection::new(async_client.clone()).vary().client_hints(ClientHints::new()))
+///     .route("/", web::get().to(|detected: DetectedDevice| async move { ... }))
+/// ```
+#[derive(Clone)]
+pub struct WmDetection {
+    _client: AsyncWmClient,
+    _vary: bool,
+    _client_hints: Option<ClientHints>,
+}
+
+impl WmDetection {
+    /// Creates a middleware that does not change responses
+    pub fn new(client: AsyncWmClient) -> WmDetection {
+        WmDetection { _client: client, _vary: false, _client_hints: None }
+    }
+
+    /// Adds to responses a `Vary` header listing `User-Agent` and, if set, the accepted client hints. Names missing from
+    /// a `Vary` header set by the service are added to it
+    pub fn vary(mut self) -> WmDetection {
+        self._vary = true;
+        self
+    }
+
+    /// Adds to responses the `Accept-CH` and `Critical-CH` headers generated by `hints`
+    pub fn client_hints(mut self, hints: ClientHints) -> WmDetection {
+        self._client_hints = Some(hints);
+        self
+    }
+
+    fn _response_headers(&self) -> Vec<(String, String)> {
+        let mut headers = vec![];
+        if self._vary {
+            let vary = match &self._client_hints {
+                Some(hints) => format!("User-Agent, {}", hints.accept_ch()),
+                None => "User-Agent".to_string(),
+            };
+            headers.push(("Vary".to_string(), vary));
+        }
+        if let Some(hints) = &self._client_hints {
+            headers.extend(hints.response_headers());
+        }
+        headers
+    }
+}
+
+impl<S, B> actix_web::dev::Transform<S, actix_web::dev::ServiceRequest> for WmDetection where
+    S: actix_web::dev::Service<actix_web::dev::ServiceRequest, Response=actix_web::dev::ServiceResponse<B>, Error=actix_web::Error> + 'static,
+    B: 'static {
+    type Response = actix_web::dev::ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Transform = WmDetectionMiddleware<S>;
+    type InitError = ();
+    type Future = std::future::Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        std::future::ready(Ok(WmDetectionMiddleware {
+            _service: std::rc::Rc::new(service),
+            _client: self._client.clone(),
+            _response_headers: Arc::new(self._response_headers()),
+        }))
+    }
+}
+
+// Stored in the request extensions by the WmDetection middleware when detection fails
+struct WmDetectionFailure(WmError);
+
+/// Service created by the `WmDetection` middleware
+pub struct WmDetectionMiddleware<S> {
+    _service: std::rc::Rc<S>,
+    _client: AsyncWmClient,
+    _response_headers: Arc<Vec<(String, String)>>,
+}
+
+impl<S, B> actix_web::dev::Service<actix_web::dev::ServiceRequest> for WmDetectionMiddleware<S> where
+    S: actix_web::dev::Service<actix_web::dev::ServiceRequest, Response=actix_web::dev::ServiceResponse<B>, Error=actix_web::Error> + 'static,
+    B: 'static {
+    type Response = actix_web::dev::ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Future = std::pin::Pin<Box<dyn std::future::Future<Output=Result<Self::Response, Self::Error>>>>;
+
+    actix_web::dev::forward_ready!(_service);
+
+    fn call(&self, req: actix_web::dev::ServiceRequest) -> Self::Future {
+        let service = self._service.clone();
+        let client = self._client.clone();
+        let response_headers = self._response_headers.clone();
+        Box::pin(async move {
+            let lookup = client.lookup_headers(req.headers().iter().map(|(name, value)| (name.as_str(), value.as_bytes())));
+            match lookup.await {
+                Ok(device) => {
+                    actix_web::HttpMessage::extensions_mut(&req).insert(DetectedDevice { device, is_fallback: false });
+                }
+                Err(wm_err) => {
+                    actix_web::HttpMessage::extensions_mut(&req).insert(WmDetectionFailure(wm_err));
+                }
+            }
+            let mut response = service.call(req).await?;
+            for (name, value) in response_headers.iter() {
+                let h_name = match actix_web::http::header::HeaderName::try_from(name.as_str()) {
+                    Ok(h_name) => h_name,
+                    Err(_) => continue,
+                };
+                if h_name == actix_web::http::header::VARY {
+                    // a Vary header set by the service is extended, rather than followed by a second one
+                    let existing: Vec<&str> = response.headers().get_all(&h_name).filter_map(|v| v.to_str().ok()).collect();
+                    if let Ok(h_value) = actix_web::http::header::HeaderValue::from_str(&_merge_vary(&existing, value)) {
+                        response.headers_mut().insert(h_name, h_value);
+                    }
+                } else if let Ok(h_value) = actix_web::http::header::HeaderValue::from_str(value) {
+                    response.headers_mut().append(h_name, h_value);
+                }
+            }
+            Ok(response)
+        })
+    }
+}
+
+/// DetectedDevice can be used as an actix-web extractor. The device stored by the `WmDetection` middleware is used
+/// when present, and the request is rejected with the middleware lookup error if detection failed. Without the
+/// middleware, the request headers are looked up with the `AsyncWmClient` registered as app data
+/// (either as `web::Data<AsyncWmClient>` or as `AsyncWmClient`).
+impl actix_web::FromRequest for DetectedDevice {
+    type Error = WmError;
+    type Future = std::pin::Pin<Box<dyn std::future::Future<Output=Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &actix_web::HttpRequest, _payload: &mut actix_web::dev::Payload) -> Self::Future {
+        let extensions = actix_web::HttpMessage::extensions(req);
+        if let Some(WmDetectionFailure(wm_err)) = extensions.get::<WmDetectionFailure>() {
+            let wm_err = WmError::with_kind(wm_err.kind, wm_err.msg.clone());
+            return Box::pin(std::future::ready(Err(wm_err)));
+        }
+        let detected = extensions.get::<DetectedDevice>().cloned();
+        drop(extensions);
+        let client = match req.app_data::<actix_web::web::Data<AsyncWmClient>>() {
+            Some(data) => Some(data.get_ref().clone()),
+            None => req.app_data::<AsyncWmClient>().cloned(),
+        };
+        let headers: Vec<(String, Vec<u8>)> = req.headers().iter()
+            .map(|(name, value)| (name.to_string(), value.as_bytes().to_vec()))
+            .collect();
+        Box::pin(async move {
+            if let Some(detected) = detected {
+                return Ok(detected);
+            }
+            match client {
+                Some(client) => Ok(DetectedDevice { device: client.lookup_headers(headers).await?, is_fallback: false }),
+                None => Err(WmError::new("No AsyncWmClient registered as app data".to_string())),
+            }
+        })
+    }
+}
+
+/// Detection errors are answered with status 503 when the WM server is unreachable, 400 when the request headers
+/// cannot be used for detection and 500 for any other error.
+impl actix_web::ResponseError for WmError {
+    fn status_code(&self) -> actix_web::http::StatusCode {
+        match self.kind {
+            WmErrorKind::Unreachable => actix_web::http::StatusCode::SERVICE_UNAVAILABLE,
+            WmErrorKind::InvalidHeader => actix_web::http::StatusCode::BAD_REQUEST,
+            _ => actix_web::http::StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+// Adds to the existing Vary header values the header names in `added` that they do not list yet, ignoring case
+fn _merge_vary(existing: &[&str], added: &str) -> String {
+    let mut names: Vec<&str> = existing.iter().flat_map(|v| v.split(',')).map(str::trim).filter(|n| !n.is_empty()).collect();
+    for name in added.split(',').map(str::trim) {
+        if !names.iter().any(|n| n.eq_ignore_ascii_case(name) || *n == "*") {
+            names.push(name);
+        }
+    }
+    names.join(", ")
+}
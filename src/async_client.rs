@@ -0,0 +1,81 @@
+/// AsyncWmClient - async wrapper of a `WmClient`, to be used from applications running on the tokio runtime.
+/// Lookups are executed on the tokio blocking thread pool, so they never block the async executor threads.
+/// Cloning an AsyncWmClient is cheap: all clones share the same `WmClient` and its cache.
+#[derive(Clone)]
+pub struct AsyncWmClient {
+    _client: Arc<WmClient>,
+}
+
+impl AsyncWmClient {
+    /// Wraps an already configured `WmClient`
+    pub fn new(client: WmClient) -> AsyncWmClient {
+        AsyncWmClient { _client: Arc::new(client) }
+    }
+
+    /// Creates a `WmClient` that connects to the given WM server (see `WmClient::new`) and wraps it
+    pub async fn create(scheme: &str, host: &str, port: &str, base_uri: &str) -> Result<AsyncWmClient, WmError> {
+        let (scheme, host, port, base_uri) = (scheme.to_string(), host.to_string(), port.to_string(), base_uri.to_string());
+        let client = AsyncWmClient::_spawn(move || WmClient::new(&scheme, &host, &port, &base_uri)).await?;
+        Ok(AsyncWmClient::new(client))
+    }
+
+    /// Returns the wrapped client
+    pub fn client(&self) -> &WmClient {
+        &self._client
+    }
+
+    /// Async version of `WmClient::lookup_useragent`
+    pub async fn lookup_useragent(&self, user_agent: String) -> Result<JSONDeviceData, WmError> {
+        let client = self._client.clone();
+        AsyncWmClient::_spawn(move || client.lookup_useragent(user_agent)).await
+    }
+
+    /// Async version of `WmClient::lookup_device_id`
+    pub async fn lookup_device_id(&self, device_id: String) -> Result<JSONDeviceData, WmError> {
+        let client = self._client.clone();
+        AsyncWmClient::_spawn(move || client.lookup_device_id(device_id)).await
+    }
+
+    /// Async version of `WmClient::lookup_headers`. Headers are copied before the lookup is started.
+    pub async fn lookup_headers<U, V, T: IntoIterator<Item=(U, V)>>(&self, in_headers: T) -> Result<JSONDeviceData, WmError> where
+        U: ToString,
+        V: AsRef<[u8]> {
+        let headers: Vec<(String, Vec<u8>)> = in_headers.into_iter()
+            .map(|(name, value)| (name.to_string(), value.as_ref().to_vec()))
+            .collect();
+        let client = self._client.clone();
+        AsyncWmClient::_spawn(move || client.lookup_headers(headers)).await
+    }
+
+    /// Async version of `WmClient::lookup_useragent_with`
+    pub async fn lookup_useragent_with(&self, caps: &CapabilitySet, user_agent: String) -> Result<JSONDeviceData, WmError> {
+        let (client, caps) = (self._client.clone(), caps.clone());
+        AsyncWmClient::_spawn(move || client.lookup_useragent_with(&caps, user_agent)).await
+    }
+
+    /// Async version of `WmClient::lookup_device_id_with`
+    pub async fn lookup_device_id_with(&self, caps: &CapabilitySet, device_id: String) -> Result<JSONDeviceData, WmError> {
+        let (client, caps) = (self._client.clone(), caps.clone());
+        AsyncWmClient::_spawn(move || client.lookup_device_id_with(&caps, device_id)).await
+    }
+
+    /// Async version of `WmClient::lookup_headers_with`. Headers are copied before the lookup is started.
+    pub async fn lookup_headers_with<U, V, T: IntoIterator<Item=(U, V)>>(&self, caps: &CapabilitySet, in_headers: T) -> Result<JSONDeviceData, WmError> where
+        U: ToString,
+        V: AsRef<[u8]> {
+        let headers: Vec<(String, Vec<u8>)> = in_headers.into_iter()
+            .map(|(name, value)| (name.to_string(), value.as_ref().to_vec()))
+            .collect();
+        let (client, caps) = (self._client.clone(), caps.clone());
+        AsyncWmClient::_spawn(move || client.lookup_headers_with(&caps, headers)).await
+    }
+
+    async fn _spawn<R, F>(f: F) -> Result<R, WmError> where
+        F: FnOnce() -> Result<R, WmError> + Send + 'static,
+        R: Send + 'static {
+        match tokio::task::spawn_blocking(f).await {
+            Ok(res) => res,
+            Err(err) => Err(WmError::new(format!("Lookup task failed: {}", err))),
+        }
+    }
+}
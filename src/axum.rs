@@ -0,0 +1,79 @@
+/// DetectedDevice can be used as an axum extractor. The device attached by `WmDetectionLayer` is used when present,
+/// otherwise the request headers are looked up with the `AsyncWmClient` taken from the router state
+/// (the state must implement `FromRef` for `AsyncWmClient`, or be the client itself).
+/// Basic usage:
+/// ```ignore
+/// async fn handler(detected: DetectedDevice) -> String {
+///     detected.device.capabilities.get("complete_device_name").cloned().unwrap_or_default()
+/// }
+/// let app = Router::new().route("/", get(handler)).with_state(async_client);
+/// ```
+/// When detection fails the request is rejected with `WmRejection`; use `DetectedDeviceWith<R>` to reject with a
+/// custom type.
+impl<S> axum_core::extract::FromRequestParts<S> for DetectedDevice where
+    AsyncWmClient: axum_core::extract::FromRef<S>,
+    S: Send + Sync {
+    type Rejection = WmRejection;
+
+    async fn from_request_parts(parts: &mut http::request::Parts, state: &S) -> Result<Self, Self::Rejection> {
+        if let Some(detected) = parts.extensions.get::<DetectedDevice>() {
+            return Ok(detected.clone());
+        }
+        let client = <AsyncWmClient as axum_core::extract::FromRef<S>>::from_ref(state);
+        let device = client.lookup_header_map(&parts.headers).await?;
+        Ok(DetectedDevice { device, is_fallback: false })
+    }
+}
+
+/// Default rejection of the `DetectedDevice` extractor. It is answered with status 503 when the WM server is unreachable,
+/// 400 when the request headers cannot be used for detection and 500 for any other error.
+#[derive(Debug)]
+pub struct WmRejection {
+    pub error: WmError,
+}
+
+impl From<WmError> for WmRejection {
+    fn from(error: WmError) -> Self {
+        WmRejection { error }
+    }
+}
+
+impl axum_core::response::IntoResponse for WmRejection {
+    fn into_response(self) -> axum_core::response::Response {
+        let status = match self.error.kind {
+            WmErrorKind::Unreachable => http::StatusCode::SERVICE_UNAVAILABLE,
+            WmErrorKind::InvalidHeader => http::StatusCode::BAD_REQUEST,
+            _ => http::StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, format!("Device detection failed: {}", self.error.msg)).into_response()
+    }
+}
+
+/// DetectedDeviceWith - same as the `DetectedDevice` extractor, but rejects requests with `R`, built from the detection error.
+/// Deref gives access to the `DetectedDevice`.
+pub struct DetectedDeviceWith<R> {
+    pub detected: DetectedDevice,
+    _rejection: std::marker::PhantomData<fn() -> R>,
+}
+
+impl<R> std::ops::Deref for DetectedDeviceWith<R> {
+    type Target = DetectedDevice;
+
+    fn deref(&self) -> &Self::Target {
+        &self.detected
+    }
+}
+
+impl<S, R> axum_core::extract::FromRequestParts<S> for DetectedDeviceWith<R> where
+    AsyncWmClient: axum_core::extract::FromRef<S>,
+    S: Send + Sync,
+    R: From<WmError> + axum_core::response::IntoResponse {
+    type Rejection = R;
+
+    async fn from_request_parts(parts: &mut http::request::Parts, state: &S) -> Result<Self, Self::Rejection> {
+        match DetectedDevice::from_request_parts(parts, state).await {
+            Ok(detected) => Ok(DetectedDeviceWith { detected, _rejection: std::marker::PhantomData }),
+            Err(rejection) => Err(R::from(rejection.error)),
+        }
+    }
+}
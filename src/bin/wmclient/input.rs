@@ -0,0 +1,67 @@
+// Parsing of the request headers read by the `headers` command
+
+/// Parses request headers given either as a JSON object (`{"User-Agent": "...", "Accept": ["a", "b"]}`) or in raw HTTP
+/// form: an optional request line followed by `Name: value` lines, up to the first empty line.
+pub fn parse_headers(text: &str) -> Result<Vec<(String, String)>, String> {
+    let trimmed = text.trim_start();
+    if trimmed.starts_with('{') {
+        return parse_json_headers(trimmed);
+    }
+    parse_raw_headers(text)
+}
+
+fn parse_json_headers(text: &str) -> Result<Vec<(String, String)>, String> {
+    let value: serde_json::Value = serde_json::from_str(text).map_err(|e| format!("Invalid JSON headers: {}", e))?;
+    let object = match value.as_object() {
+        Some(object) => object,
+        None => return Err("JSON headers must be an object".to_string()),
+    };
+    let mut headers = vec![];
+    for (name, value) in object {
+        match value {
+            serde_json::Value::String(s) => headers.push((name.clone(), s.clone())),
+            // repeated headers
+            serde_json::Value::Array(values) => {
+                for v in values {
+                    match v.as_str() {
+                        Some(s) => headers.push((name.clone(), s.to_string())),
+                        None => return Err(format!("Invalid value for header {}: values must be strings", name)),
+                    }
+                }
+            }
+            _ => return Err(format!("Invalid value for header {}: values must be strings", name)),
+        }
+    }
+    Ok(headers)
+}
+
+fn parse_raw_headers(text: &str) -> Result<Vec<(String, String)>, String> {
+    let mut headers = vec![];
+    let mut started = false;
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() {
+            // leading empty lines are skipped, the first one after the headers ends the header section
+            if started {
+                break;
+            }
+            continue;
+        }
+        let first = !started;
+        started = true;
+        if first && is_request_line(line) {
+            continue;
+        }
+        match line.split_once(':') {
+            Some((name, value)) if !name.trim().is_empty() => headers.push((name.trim().to_string(), value.trim().to_string())),
+            _ => return Err(format!("Invalid header at line {}: {}", index + 1, line)),
+        }
+    }
+    Ok(headers)
+}
+
+// Request line, such as "GET /index.html HTTP/1.1"
+fn is_request_line(line: &str) -> bool {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    parts.len() == 3 && parts[2].starts_with("HTTP/")
+}
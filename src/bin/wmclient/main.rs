@@ -0,0 +1,369 @@
+// wmclient - command line tool to perform device detections and inspect a WURFL Microservice server.
+// Build it with the `cli` feature: cargo install wmclient --features cli
+
+mod input;
+mod output;
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::Serialize;
+use wmclient::{BrandModel, CapabilityParsing, CapabilitySet, DeviceDiff, DeviceStatistics, EnumerationDiff, EnumerationSnapshot, JSONDeviceData,
+               LogEnricher, LogFormat, MakeModel, OsNameVersion, WmClient, WmError, WmErrorKind, STATISTICS_CAPABILITIES};
+
+use output::Format;
+
+#[derive(Parser)]
+#[command(name = "wmclient", version, about = "WURFL Microservice command line client")]
+struct Cli {
+    /// WM server URL, as scheme://host:port[/base_uri]
+    #[arg(long, global = true, env = "WMCLIENT_URL", default_value = "http://localhost:8080")]
+    url: String,
+
+    /// Replays the server responses recorded in a fixture file instead of contacting the server
+    #[arg(long, global = true, value_name = "FIXTURE")]
+    replay: Option<String>,
+
+    /// Capabilities returned by lookups, comma or whitespace separated (default: all)
+    #[arg(long, global = true)]
+    caps: Option<String>,
+
+    /// Maximum number of User-Agent lookups kept in the client cache
+    #[arg(long, global = true, default_value_t = 100000)]
+    cache_size: usize,
+
+    /// Prints results as JSON
+    #[arg(long, global = true, conflicts_with_all = ["table", "csv"])]
+    json: bool,
+
+    /// Prints results as CSV
+    #[arg(long, global = true, conflicts_with = "table")]
+    csv: bool,
+
+    /// Prints results as a text table (default)
+    #[arg(long, global = true)]
+    table: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Prints the server info
+    Info,
+    /// Detects the device of a User-Agent
+    Ua {
+        user_agent: String,
+    },
+    /// Prints the device with the given WURFL ID
+    Id {
+        wurfl_id: String,
+    },
+    /// Detects the device from request headers read from stdin, in raw HTTP or JSON object form
+    Headers,
+    /// Lists all device brands
+    Makes,
+    /// Lists the models of a brand
+    Models {
+        brand: String,
+    },
+    /// Lists all device operating systems
+    Oses,
+    /// Lists the versions of an operating system
+    Versions {
+        os: String,
+    },
+    /// Adds the detected device capabilities to the records of an access log
+    Enrich {
+        /// Access log format
+        #[arg(long, value_enum, default_value = "combined")]
+        format: EnrichFormat,
+        /// JSON field or CSV column holding the User-Agent
+        #[arg(long, default_value = "user_agent")]
+        ua_field: String,
+        /// Log file to enrich (default: stdin)
+        input: Option<String>,
+        /// File where the enriched log is written (default: stdout)
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// Prints the traffic shares by form factor, brand, OS and bot vs human of a file with one User-Agent per line
+    Stats {
+        /// User-Agent file (default: stdin)
+        input: Option<String>,
+    },
+    /// Saves the makes, models, OSes and versions of the server to a snapshot file
+    Snapshot {
+        /// File where the snapshot is written (default: stdout)
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// Prints the makes, models, marketing names, OSes and versions added or removed between two snapshots
+    Diff {
+        /// Snapshot of the old data
+        old: String,
+        /// Snapshot of the new data (default: the current server data)
+        new: Option<String>,
+    },
+    /// Detects the devices of a file with one User-Agent per line on two servers and prints the capabilities that differ
+    Compare {
+        /// URL of the server compared with the one of --url, as scheme://host:port[/base_uri]
+        #[arg(long, required_unless_present = "against_replay")]
+        against: Option<String>,
+        /// Replays the responses of the compared server recorded in a fixture file
+        #[arg(long, value_name = "FIXTURE", conflicts_with = "against")]
+        against_replay: Option<String>,
+        /// User-Agent file (default: stdin)
+        input: Option<String>,
+    },
+}
+
+// Capabilities that differ for a User-Agent, as printed by the compare command
+#[derive(Serialize)]
+struct UserAgentDiff {
+    user_agent: String,
+    diff: DeviceDiff,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum EnrichFormat {
+    /// Combined Log Format
+    Combined,
+    /// JSON lines
+    Jsonl,
+    /// CSV with a header record
+    Csv,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(&cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("wmclient: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: &Cli) -> Result<(), WmError> {
+    let format = if cli.json {
+        Format::Json
+    } else if cli.csv {
+        Format::Csv
+    } else {
+        Format::Table
+    };
+    // comparing two snapshot files does not need a server
+    if let Command::Diff { old, new: Some(new) } = &cli.command {
+        print_diff(format, &EnumerationDiff::between(&read_snapshot(old)?, &read_snapshot(new)?));
+        return Ok(());
+    }
+    let client = create_client(cli, &cli.url, &cli.replay)?;
+    match &cli.command {
+        Command::Info => {
+            let info = client.get_info()?;
+            let rows = vec![
+                vec!["WM server version".to_string(), info.wm_version.clone()],
+                vec!["WURFL API version".to_string(), info.wurfl_api_version.clone()],
+                vec!["WURFL info".to_string(), info.wurfl_info.clone()],
+                vec!["Important headers".to_string(), info.important_headers.join(", ")],
+                vec!["Static capabilities".to_string(), info.static_caps.len().to_string()],
+                vec!["Virtual capabilities".to_string(), info.virtual_caps.len().to_string()],
+            ];
+            output::print(format, &info, rows, false);
+        }
+        Command::Ua { user_agent } => print_device(format, &client.lookup_useragent(user_agent.clone())?),
+        Command::Id { wurfl_id } => print_device(format, &client.lookup_device_id(wurfl_id.clone())?),
+        Command::Headers => {
+            let mut text = String::new();
+            if let Err(err) = std::io::stdin().read_to_string(&mut text) {
+                return Err(WmError::new(format!("Unable to read headers from stdin: {}", err)));
+            }
+            let headers = input::parse_headers(&text).map_err(WmError::new)?;
+            print_device(format, &client.lookup_headers(headers)?);
+        }
+        Command::Makes => print_list(format, client.get_all_device_makes()?),
+        Command::Models { brand } => {
+            let models = client.get_all_devices_for_make(brand.clone())?;
+            let mut rows = vec![vec!["Model".to_string(), "Marketing name".to_string()]];
+            rows.extend(models.iter().map(|m| vec![m.model_name.clone(), m.marketing_name.clone()]));
+            output::print(format, &models, rows, true);
+        }
+        Command::Oses => print_list(format, client.get_all_oses()?),
+        Command::Versions { os } => {
+            // already sorted from the oldest version
+            let versions = client.get_all_versions_for_os(os)?;
+            let rows = versions.iter().map(|v| vec![v.clone()]).collect();
+            output::print(format, &versions, rows, false);
+        }
+        Command::Enrich { format: log_format, ua_field, input, output } => {
+            let log_format = match log_format {
+                EnrichFormat::Combined => LogFormat::Combined,
+                EnrichFormat::Jsonl => LogFormat::JsonLines { ua_field: ua_field.clone() },
+                EnrichFormat::Csv => LogFormat::Csv { ua_column: ua_field.clone() },
+            };
+            let reader = open_input(input)?;
+            let writer: Box<dyn Write> = match output {
+                Some(path) => Box::new(BufWriter::new(File::create(path).map_err(|e| WmError::new(format!("Unable to create {}: {}", path, e)))?)),
+                None => Box::new(BufWriter::new(std::io::stdout().lock())),
+            };
+            let stats = LogEnricher::new(&client, log_format).enrich(reader, writer)?;
+            eprintln!("{} records, {} enriched, {} skipped, {} lookup errors", stats.records, stats.enriched, stats.skipped, stats.errors);
+        }
+        Command::Stats { input } => {
+            let caps = CapabilitySet::try_new(&client, STATISTICS_CAPABILITIES.to_vec(), CapabilityParsing::Strict)?;
+            let mut stats = DeviceStatistics::new();
+            let mut errors = 0;
+            for line in open_input(input)?.lines() {
+                let user_agent = line.map_err(|e| WmError::new(format!("Unable to read User-Agents: {}", e)))?;
+                if user_agent.trim().is_empty() {
+                    continue;
+                }
+                match client.lookup_useragent_with(&caps, user_agent) {
+                    Ok(device) => stats.add(&device),
+                    Err(err) if err.kind == WmErrorKind::Unreachable => return Err(err),
+                    Err(_) => errors += 1,
+                }
+            }
+            let report = stats.report();
+            let mut rows = vec![vec!["Dimension".to_string(), "Value".to_string(), "Count".to_string(), "Percentage".to_string()]];
+            for (dimension, entries) in report.dimensions() {
+                rows.extend(entries.iter().map(|e| vec![dimension.to_string(), e.value.clone(), e.count.to_string(), format!("{:.2}", e.percentage)]));
+            }
+            output::print(format, &report, rows, true);
+            eprintln!("{} User-Agents, {} lookup errors", report.total, errors);
+        }
+        Command::Snapshot { output } => {
+            let writer: Box<dyn Write> = match output {
+                Some(path) => Box::new(BufWriter::new(File::create(path).map_err(|e| WmError::new(format!("Unable to create {}: {}", path, e)))?)),
+                None => Box::new(BufWriter::new(std::io::stdout().lock())),
+            };
+            client.export_enumerations(writer)?;
+        }
+        Command::Diff { old, .. } => print_diff(format, &client.diff_enumerations(&read_snapshot(old)?)?),
+        Command::Compare { against, against_replay, input } => {
+            let other = create_client(cli, against.as_deref().unwrap_or_default(), against_replay)?;
+            let mut diffs = vec![];
+            let (mut total, mut errors) = (0, 0);
+            for line in open_input(input)?.lines() {
+                let user_agent = line.map_err(|e| WmError::new(format!("Unable to read User-Agents: {}", e)))?;
+                if user_agent.trim().is_empty() {
+                    continue;
+                }
+                total += 1;
+                let devices = client.lookup_useragent(user_agent.clone())
+                    .and_then(|device| Ok((device, other.lookup_useragent(user_agent.clone())?)));
+                match devices {
+                    Ok((device, other_device)) => {
+                        let diff = DeviceDiff::between(&device, &other_device);
+                        if !diff.is_empty() {
+                            diffs.push(UserAgentDiff { user_agent, diff });
+                        }
+                    }
+                    Err(err) if err.kind == WmErrorKind::Unreachable => return Err(err),
+                    Err(_) => errors += 1,
+                }
+            }
+            print_device_diffs(format, &diffs);
+            eprintln!("{} User-Agents, {} with differences, {} lookup errors", total, diffs.len(), errors);
+        }
+    }
+    Ok(())
+}
+
+fn create_client(cli: &Cli, url: &str, replay: &Option<String>) -> Result<WmClient, WmError> {
+    let mut client = match replay {
+        Some(fixture) => WmClient::new_replaying(fixture)?,
+        None => {
+            let (scheme, host, port, base_uri) = parse_url(url)?;
+            WmClient::new(&scheme, &host, &port, &base_uri)?
+        }
+    };
+    client.set_cache_size(cli.cache_size);
+    if let Some(caps) = &cli.caps {
+        client.try_set_requested_capabilities(caps.split(',').collect(), CapabilityParsing::Lenient)?;
+    }
+    Ok(client)
+}
+
+// Splits a scheme://host:port/base_uri URL in the parts used to create a WmClient
+fn parse_url(url: &str) -> Result<(String, String, String, String), WmError> {
+    let (scheme, rest) = match url.split_once("://") {
+        Some((scheme, rest)) if scheme == "http" || scheme == "https" => (scheme, rest),
+        _ => return Err(WmError::new(format!("Invalid server URL {}: it must start with http:// or https://", url))),
+    };
+    let (host_port, base_uri) = match rest.split_once('/') {
+        Some((host_port, base_uri)) => (host_port, base_uri.trim_end_matches('/')),
+        None => (rest, ""),
+    };
+    let (host, port) = match host_port.rsplit_once(':') {
+        Some((host, port)) => (host, port),
+        None => (host_port, if scheme == "https" { "443" } else { "80" }),
+    };
+    if host.is_empty() || port.parse::<u16>().is_err() {
+        return Err(WmError::new(format!("Invalid server URL {}", url)));
+    }
+    Ok((scheme.to_string(), host.to_string(), port.to_string(), base_uri.to_string()))
+}
+
+fn read_snapshot(path: &str) -> Result<EnumerationSnapshot, WmError> {
+    let file = File::open(path).map_err(|e| WmError::new(format!("Unable to open {}: {}", path, e)))?;
+    EnumerationSnapshot::read(BufReader::new(file))
+}
+
+fn print_diff(format: Format, diff: &EnumerationDiff) {
+    let mut rows = vec![vec!["Change".to_string(), "Kind".to_string(), "Value".to_string()]];
+    let mut add = |kind: &str, added: Vec<String>, removed: Vec<String>| {
+        rows.extend(added.into_iter().map(|v| vec!["+".to_string(), kind.to_string(), v]));
+        rows.extend(removed.into_iter().map(|v| vec!["-".to_string(), kind.to_string(), v]));
+    };
+    let model = |m: &BrandModel| format!("{} {}", m.brand_name, m.model_name);
+    let marketing_name = |m: &MakeModel| format!("{} {}: {}", m.brand_name, m.model_name, m.marketing_name.clone().unwrap_or_default());
+    let os_version = |v: &OsNameVersion| format!("{} {}", v.device_os, v.device_os_version);
+    add("brand", diff.brands.added.clone(), diff.brands.removed.clone());
+    add("model", diff.models.added.iter().map(model).collect(), diff.models.removed.iter().map(model).collect());
+    add("marketing name", diff.marketing_names.added.iter().map(marketing_name).collect(), diff.marketing_names.removed.iter().map(marketing_name).collect());
+    add("os", diff.oses.added.clone(), diff.oses.removed.clone());
+    add("os version", diff.os_versions.added.iter().map(os_version).collect(), diff.os_versions.removed.iter().map(os_version).collect());
+    output::print(format, diff, rows, true);
+}
+
+fn print_device_diffs(format: Format, diffs: &[UserAgentDiff]) {
+    let mut rows = vec![vec!["User-Agent".to_string(), "Change".to_string(), "Capability".to_string(), "Old value".to_string(), "New value".to_string()]];
+    for ua_diff in diffs {
+        let mut row = |change: &str, name: &str, old_value: &str, new_value: &str| {
+            rows.push(vec![ua_diff.user_agent.clone(), change.to_string(), name.to_string(), old_value.to_string(), new_value.to_string()]);
+        };
+        let diff = &ua_diff.diff;
+        diff.added.iter().for_each(|(name, value)| row("+", name, "", value));
+        diff.removed.iter().for_each(|(name, value)| row("-", name, value, ""));
+        diff.changed.iter().for_each(|c| row("~", &c.name, &c.old_value, &c.new_value));
+    }
+    output::print(format, &diffs, rows, true);
+}
+
+// Opens a file, or stdin when no path is given
+fn open_input(path: &Option<String>) -> Result<Box<dyn BufRead>, WmError> {
+    Ok(match path {
+        Some(path) => Box::new(BufReader::new(File::open(path).map_err(|e| WmError::new(format!("Unable to open {}: {}", path, e)))?)),
+        None => Box::new(std::io::stdin().lock()),
+    })
+}
+
+fn print_device(format: Format, device: &JSONDeviceData) {
+    let mut names: Vec<&String> = device.capabilities.keys().collect();
+    names.sort();
+    let mut rows = vec![vec!["Capability".to_string(), "Value".to_string()]];
+    rows.extend(names.iter().map(|n| vec![n.to_string(), device.capabilities[*n].clone()]));
+    output::print(format, device, rows, true);
+}
+
+fn print_list(format: Format, mut items: Vec<String>) {
+    items.sort();
+    let rows = items.iter().map(|i| vec![i.clone()]).collect();
+    output::print(format, &items, rows, false);
+}
@@ -0,0 +1,63 @@
+// Printing of the command results, as aligned text tables, CSV or JSON
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Table,
+    Json,
+    Csv,
+}
+
+/// Prints `value` as pretty JSON, or `rows` as a table or CSV (the first row is the header, if `header` is true)
+pub fn print<T: Serialize>(format: Format, value: &T, rows: Vec<Vec<String>>, header: bool) {
+    match format {
+        Format::Json => match serde_json::to_string_pretty(value) {
+            Ok(json) => println!("{}", json),
+            Err(err) => eprintln!("Unable to print JSON output: {}", err),
+        },
+        Format::Table => print!("{}", table(&rows, header)),
+        Format::Csv => {
+            let mut writer = csv::WriterBuilder::new().flexible(true).from_writer(std::io::stdout());
+            for row in &rows {
+                if let Err(err) = writer.write_record(row) {
+                    eprintln!("Unable to print CSV output: {}", err);
+                    return;
+                }
+            }
+            let _ = writer.flush();
+        }
+    }
+}
+
+/// Formats rows as a table with left aligned columns, separated by two spaces
+pub fn table(rows: &[Vec<String>], header: bool) -> String {
+    let columns = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+    let mut widths = vec![0; columns];
+    for row in rows {
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(cell.chars().count());
+        }
+    }
+    let mut out = String::new();
+    for (index, row) in rows.iter().enumerate() {
+        let mut line = String::new();
+        for (i, cell) in row.iter().enumerate() {
+            if i > 0 {
+                line.push_str("  ");
+            }
+            line.push_str(cell);
+            if i + 1 < row.len() {
+                line.push_str(&" ".repeat(widths[i] - cell.chars().count()));
+            }
+        }
+        out.push_str(&line);
+        out.push('\n');
+        if header && index == 0 {
+            let total: usize = widths.iter().sum::<usize>() + 2 * columns.saturating_sub(1);
+            out.push_str(&"-".repeat(total));
+            out.push('\n');
+        }
+    }
+    out
+}
@@ -1,6 +1,8 @@
 pub struct Cache {
     _ua_cache: Arc<Mutex<LruCache<String, JSONDeviceData>>>,
     _dev_id_cache: Arc<Mutex<LruCache<String, JSONDeviceData>>>,
+    _hits: AtomicU64,
+    _misses: AtomicU64,
 }
 
 impl Cache {
@@ -8,6 +10,8 @@ impl Cache {
         Cache {
             _ua_cache: Arc::new(Mutex::new(LruCache::new(max_size))),
             _dev_id_cache: Arc::new(Mutex::new(LruCache::new(20000))),
+            _hits: AtomicU64::new(0),
+            _misses: AtomicU64::new(0),
         }
     }
 
@@ -43,15 +47,30 @@ impl Cache {
     }
 
     pub fn get(&self, cache_type: String, key: String) -> Option<JSONDeviceData> {
-        if cache_type == USERAGENT_CACHE_TYPE {
-            return _internal_get(&self._ua_cache, key);
+        let device = if cache_type == USERAGENT_CACHE_TYPE {
+            _internal_get(&self._ua_cache, key)
         } else if cache_type == DEVICE_ID_CACHE_TYPE {
-             return _internal_get(&self._dev_id_cache, key);
-        }
-        None
+            _internal_get(&self._dev_id_cache, key)
+        } else {
+            return None;
+        };
+        let counter = if device.is_some() { &self._hits } else { &self._misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        device
+    }
+
+    /// Returns the number of cache hits and misses of the `get` calls done on this cache
+    pub fn get_hits_and_misses(&self) -> (u64, u64) {
+        (self._hits.load(Ordering::Relaxed), self._misses.load(Ordering::Relaxed))
+    }
+
+    /// Returns the maximum number of entries of the user-agent/headers cache
+    pub fn get_max_size(&self) -> usize {
+        self._ua_cache.lock().unwrap().cap()
     }
 }
 
+#[allow(clippy::unnecessary_unwrap, clippy::needless_return)]
 fn _internal_get(cache: &Arc<Mutex<LruCache<String, JSONDeviceData>>>, key: String) -> Option<JSONDeviceData> {
     let mut cache_guard = cache.lock().unwrap();
 
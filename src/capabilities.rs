@@ -0,0 +1,148 @@
+/// CapabilitySet - static and virtual capabilities requested by a single lookup, used by the `lookup_*_with`
+/// client methods. Unlike `set_requested_capabilities`, it does not change the client state nor clear its caches:
+/// lookups done with different sets are cached separately, so several sets can be used with the same client.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CapabilitySet {
+    static_caps: Vec<String>,
+    virtual_caps: Vec<String>,
+}
+
+impl CapabilitySet {
+    /// Creates a capability set from a list of static and/or virtual capability names: as `set_requested_capabilities`
+    /// does, names are assigned to the set they belong to according to the server info of `client`, unknown names are ignored.
+    /// Note that a list of unknown names only gives an empty set, that requests all the capabilities: use `try_new` to
+    /// reject them instead.
+    pub fn new(client: &WmClient, cap_list: Vec<&str>) -> CapabilitySet {
+        let mut static_caps: Vec<String> = vec![];
+        let mut virtual_caps: Vec<String> = vec![];
+        for name in cap_list {
+            if client.has_static_capability(name) {
+                static_caps.push(name.to_string());
+            } else if client.has_virtual_capability(name) {
+                virtual_caps.push(name.to_string());
+            }
+        }
+        // sorted names make sets with the same capabilities equal, whatever the order they are listed in
+        static_caps.sort();
+        static_caps.dedup();
+        virtual_caps.sort();
+        virtual_caps.dedup();
+        CapabilitySet { static_caps, virtual_caps }
+    }
+
+    /// Requested static capability names
+    pub fn static_caps(&self) -> &[String] {
+        &self.static_caps
+    }
+
+    /// Requested virtual capability names
+    pub fn virtual_caps(&self) -> &[String] {
+        &self.virtual_caps
+    }
+
+    /// Returns true if no capability is in the set: lookups done with an empty set return all the capabilities
+    pub fn is_empty(&self) -> bool {
+        self.static_caps.is_empty() && self.virtual_caps.is_empty()
+    }
+
+    // Requested static and virtual capabilities sent to the server: as for the client ones, empty lists request all capabilities
+    fn _requested(&self) -> (Option<Vec<String>>, Option<Vec<String>>) {
+        (Some(self.static_caps.clone()), Some(self.virtual_caps.clone()))
+    }
+
+    // Appended to the cache keys of the devices looked up with this set
+    fn _cache_key(&self) -> String {
+        format!("|{}|{}", self.static_caps.join(","), self.virtual_caps.join(","))
+    }
+}
+
+/// How capability names passed to the validating methods (`try_set_requested_capabilities`, `CapabilitySet::try_new`) are read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CapabilityParsing {
+    /// Each entry must be exactly a capability name
+    Strict,
+    /// Entries may hold several whitespace separated capability names, such as `"model_name brand_name"`
+    Lenient,
+}
+
+impl CapabilitySet {
+    /// Creates a capability set from a list of static and/or virtual capability names, returning a
+    /// `WmErrorKind::InvalidCapability` error that lists the names unknown to the server of `client`, with suggestions.
+    pub fn try_new(client: &WmClient, cap_list: Vec<&str>, parsing: CapabilityParsing) -> Result<CapabilitySet, WmError> {
+        let names = client._validate_capabilities(cap_list, parsing, true, true)?;
+        Ok(CapabilitySet::new(client, names.iter().map(|n| n.as_str()).collect()))
+    }
+}
+
+impl WmClient {
+    // Returns the capability names in cap_list, or an InvalidCapability error listing the ones that are not among
+    // the allowed (static and/or virtual) capabilities, each one with a "did you mean" suggestion if any is close enough.
+    fn _validate_capabilities(&self, cap_list: Vec<&str>, parsing: CapabilityParsing, allow_static: bool, allow_virtual: bool) -> Result<Vec<String>, WmError> {
+        let names: Vec<&str> = match parsing {
+            CapabilityParsing::Strict => cap_list,
+            CapabilityParsing::Lenient => cap_list.iter().flat_map(|entry| entry.split_whitespace()).collect(),
+        };
+        let mut candidates: Vec<&String> = vec![];
+        if allow_static {
+            candidates.extend(self.static_caps.iter());
+        }
+        if allow_virtual {
+            candidates.extend(self.virtual_caps.iter());
+        }
+
+        let mut valid: Vec<String> = vec![];
+        let mut unknown: Vec<String> = vec![];
+        for name in names {
+            if candidates.iter().any(|c| c.as_str() == name) {
+                valid.push(name.to_string());
+            } else {
+                unknown.push(self._describe_unknown_capability(name, &candidates, allow_static, allow_virtual));
+            }
+        }
+        if !unknown.is_empty() {
+            return Err(WmError::with_kind(WmErrorKind::InvalidCapability, format!("Unknown capabilities: {}", unknown.join(", "))));
+        }
+        Ok(valid)
+    }
+
+    fn _describe_unknown_capability(&self, name: &str, candidates: &[&String], allow_static: bool, allow_virtual: bool) -> String {
+        // a whitespace separated list of valid names passed as a single entry
+        let parts: Vec<&str> = name.split_whitespace().collect();
+        if parts.len() > 1 && parts.iter().all(|p| candidates.iter().any(|c| c.as_str() == *p)) {
+            return format!("\"{}\" (did you mean to pass {} as separate names?)", name, parts.join(", "));
+        }
+        // a valid name of the other kind
+        if !allow_static && self.has_static_capability(name) {
+            return format!("{} (is a static capability)", name);
+        }
+        if !allow_virtual && self.has_virtual_capability(name) {
+            return format!("{} (is a virtual capability)", name);
+        }
+        // the closest name, if it differs by no more than a third of its length
+        let max_distance = std::cmp::max(2, name.chars().count() / 3);
+        let closest = candidates.iter()
+            .map(|c| (_edit_distance(name, c), *c))
+            .filter(|(d, _)| *d <= max_distance)
+            .min();
+        match closest {
+            Some((_, suggestion)) => format!("{} (did you mean {}?)", name, suggestion),
+            None => name.to_string(),
+        }
+    }
+}
+
+// Levenshtein distance between two strings
+fn _edit_distance(a: &str, b: &str) -> usize {
+    let b_chars: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b_chars.len()).collect();
+    let mut current: Vec<usize> = vec![0; b_chars.len() + 1];
+    for (i, a_char) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, b_char) in b_chars.iter().enumerate() {
+            let substitution = prev[j] + if a_char == *b_char { 0 } else { 1 };
+            current[j + 1] = std::cmp::min(substitution, std::cmp::min(prev[j + 1], current[j]) + 1);
+        }
+        std::mem::swap(&mut prev, &mut current);
+    }
+    prev[b_chars.len()]
+}
@@ -0,0 +1,273 @@
+// Metadata of the best known WURFL capabilities, embedded in the crate
+const CAPABILITY_INFO_JSON: &str = include_str!("./capability_info.json");
+
+/// Type of the values of a capability. The WM server sends all values as strings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CapabilityType {
+    /// "true" or "false"
+    Bool,
+    /// An integer number, such as a screen width
+    Int,
+    /// One of a known list of values, see `CapabilityInfo::enum_values`
+    Enum,
+    /// Any string, possibly empty
+    String,
+}
+
+/// CapabilityInfo - what a WURFL capability holds: its value type, the group it belongs to and a description.
+/// See `CapabilityRegistry`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapabilityInfo {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub value_type: CapabilityType,
+    /// WURFL group of the capability ("product_info", "display"...), "virtual" for virtual capabilities
+    pub group: String,
+    #[serde(rename = "virtual")]
+    pub is_virtual: bool,
+    /// Allowed values, for `CapabilityType::Enum` capabilities only
+    pub enum_values: Vec<String>,
+    pub description: String,
+}
+
+/// A capability value converted to its type. Values of `Enum` capabilities that are not in the known list, for
+/// example added by a newer WURFL data release, are returned as `String`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CapabilityValue {
+    Bool(bool),
+    Int(i64),
+    Enum(String),
+    String(String),
+}
+
+impl CapabilityValue {
+    /// The value of a `Bool` capability
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            CapabilityValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// The value of an `Int` capability
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            CapabilityValue::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// The value of an `Enum` or `String` capability
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            CapabilityValue::Enum(value) | CapabilityValue::String(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for CapabilityValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CapabilityValue::Bool(value) => write!(f, "{}", value),
+            CapabilityValue::Int(value) => write!(f, "{}", value),
+            CapabilityValue::Enum(value) | CapabilityValue::String(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+impl CapabilityInfo {
+    /// Converts a value received from the WM server to the capability type, returning a
+    /// `WmErrorKind::InvalidValue` error if it is not valid for it. Enum values missing from `enum_values` are
+    /// not an error: they are returned as `CapabilityValue::String`
+    pub fn parse(&self, value: &str) -> Result<CapabilityValue, WmError> {
+        let parsed = match self.value_type {
+            CapabilityType::Bool => match value {
+                "true" => Some(CapabilityValue::Bool(true)),
+                "false" => Some(CapabilityValue::Bool(false)),
+                _ => None,
+            },
+            CapabilityType::Int => value.parse::<i64>().ok().map(CapabilityValue::Int),
+            CapabilityType::Enum if self.enum_values.iter().any(|v| v == value) => Some(CapabilityValue::Enum(value.to_string())),
+            CapabilityType::Enum | CapabilityType::String => Some(CapabilityValue::String(value.to_string())),
+        };
+        parsed.ok_or_else(|| WmError::with_kind(WmErrorKind::InvalidValue,
+                                                format!("Invalid value \"{}\" for {} capability {}", value, self._type_name(), self.name)))
+    }
+
+    fn _type_name(&self) -> &str {
+        match self.value_type {
+            CapabilityType::Bool => "bool",
+            CapabilityType::Int => "int",
+            CapabilityType::Enum => "enum",
+            CapabilityType::String => "string",
+        }
+    }
+}
+
+/// CapabilityRegistry - metadata of WURFL capabilities, by name. The embedded registry, shipped with this crate,
+/// covers the most used capabilities: `check` tells which capabilities of a WM server it does not know.
+/// Basic usage:
+/// ```no_run
+/// use wmclient::{CapabilityRegistry, WmClient};
+/// let client = WmClient::new("http", "localhost", "8080", "").unwrap();
+/// let device = client.lookup_useragent("Mozilla/5.0 (Linux; Android 10; SM-G960F)".to_string()).unwrap();
+/// let width = device.typed_capability("resolution_width").unwrap().as_int();
+/// for info in CapabilityRegistry::embedded().in_group("display") {
+///     println!("{}: {}", info.name, info.description);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CapabilityRegistry {
+    // sorted by name
+    _infos: Vec<CapabilityInfo>,
+}
+
+/// Result of `CapabilityRegistry::check`: differences between the registry and the capabilities of a WM server
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RegistryCheck {
+    /// Capabilities of the server missing from the registry
+    pub unknown: Vec<String>,
+    /// Capabilities of the registry the server does not expose
+    pub missing: Vec<String>,
+    /// Capabilities that are static in the registry and virtual on the server, or the other way round
+    pub wrong_kind: Vec<String>,
+}
+
+impl RegistryCheck {
+    /// True if the registry has metadata for all the server capabilities, of the right kind
+    pub fn is_consistent(&self) -> bool {
+        self.unknown.is_empty() && self.wrong_kind.is_empty()
+    }
+}
+
+impl CapabilityRegistry {
+    /// Creates a registry from a list of capability metadata; for a name listed more than once, the last one is kept
+    pub fn new(infos: Vec<CapabilityInfo>) -> CapabilityRegistry {
+        let mut infos = infos;
+        infos.reverse();
+        infos.sort_by(|a, b| a.name.cmp(&b.name));
+        infos.dedup_by(|a, b| a.name == b.name);
+        CapabilityRegistry { _infos: infos }
+    }
+
+    /// The registry embedded in this crate
+    pub fn embedded() -> &'static CapabilityRegistry {
+        static EMBEDDED: std::sync::OnceLock<CapabilityRegistry> = std::sync::OnceLock::new();
+        EMBEDDED.get_or_init(|| {
+            let infos: Vec<CapabilityInfo> = serde_json::from_str(CAPABILITY_INFO_JSON).expect("embedded capability metadata is valid");
+            CapabilityRegistry::new(infos)
+        })
+    }
+
+    /// Metadata of the named capability
+    pub fn get(&self, name: &str) -> Option<&CapabilityInfo> {
+        self._infos.binary_search_by(|i| i.name.as_str().cmp(name)).ok().map(|index| &self._infos[index])
+    }
+
+    /// Iterates over the capabilities, in alphabetical order
+    pub fn iter(&self) -> std::slice::Iter<'_, CapabilityInfo> {
+        self._infos.iter()
+    }
+
+    /// Number of capabilities
+    pub fn len(&self) -> usize {
+        self._infos.len()
+    }
+
+    /// True if there are no capabilities
+    pub fn is_empty(&self) -> bool {
+        self._infos.is_empty()
+    }
+
+    /// Names of the groups, in alphabetical order
+    pub fn groups(&self) -> Vec<&str> {
+        let groups: std::collections::BTreeSet<&str> = self._infos.iter().map(|i| i.group.as_str()).collect();
+        groups.into_iter().collect()
+    }
+
+    /// Capabilities of the given group, in alphabetical order
+    pub fn in_group(&self, group: &str) -> Vec<&CapabilityInfo> {
+        self._infos.iter().filter(|i| i.group == group).collect()
+    }
+
+    /// Compares the registry with the static and virtual capabilities listed in the info of a WM server
+    pub fn check(&self, info: &JSONInfoData) -> RegistryCheck {
+        let mut check = RegistryCheck::default();
+        let server_caps = info.static_caps.iter().map(|name| (name, false)).chain(info.virtual_caps.iter().map(|name| (name, true)));
+        for (name, is_virtual) in server_caps {
+            match self.get(name) {
+                Some(cap_info) if cap_info.is_virtual != is_virtual => check.wrong_kind.push(name.clone()),
+                Some(_) => {}
+                None => check.unknown.push(name.clone()),
+            }
+        }
+        for cap_info in &self._infos {
+            let server_names = if cap_info.is_virtual { &info.virtual_caps } else { &info.static_caps };
+            if !server_names.contains(&cap_info.name) && !check.wrong_kind.contains(&cap_info.name) {
+                check.missing.push(cap_info.name.clone());
+            }
+        }
+        check.unknown.sort();
+        check.wrong_kind.sort();
+        check
+    }
+}
+
+impl<'a> IntoIterator for &'a CapabilityRegistry {
+    type Item = &'a CapabilityInfo;
+    type IntoIter = std::slice::Iter<'a, CapabilityInfo>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl JSONDeviceData {
+    /// Returns the value of the named capability converted to its type, as listed in the embedded `CapabilityRegistry`.
+    /// Values of capabilities missing from the registry are returned as `CapabilityValue::String`.
+    /// Returns a `WmErrorKind::InvalidCapability` error if the device data has no such capability (it may not have
+    /// been requested) and a `WmErrorKind::InvalidValue` error if the value is not valid for the capability type.
+    pub fn typed_capability(&self, name: &str) -> Result<CapabilityValue, WmError> {
+        let value = match self.capabilities.get(name) {
+            Some(value) => value,
+            None => return Err(WmError::with_kind(WmErrorKind::InvalidCapability, format!("Capability {} is not in the device data", name))),
+        };
+        match CapabilityRegistry::embedded().get(name) {
+            Some(cap_info) => cap_info.parse(value),
+            None => Ok(CapabilityValue::String(value.clone())),
+        }
+    }
+
+    /// Checks all the capability values against the embedded `CapabilityRegistry`, returning a
+    /// `WmErrorKind::InvalidValue` error that lists the invalid ones
+    pub fn validate_capabilities(&self) -> Result<(), WmError> {
+        let registry = CapabilityRegistry::embedded();
+        let mut invalid: Vec<String> = self.capabilities.iter()
+            .filter_map(|(name, value)| registry.get(name).and_then(|cap_info| cap_info.parse(value).err()))
+            .map(|err| err.msg)
+            .collect();
+        if invalid.is_empty() {
+            return Ok(());
+        }
+        invalid.sort();
+        Err(WmError::with_kind(WmErrorKind::InvalidValue, invalid.join("; ")))
+    }
+}
+
+impl WmClient {
+    /// Returns the metadata of a capability exposed by the WM server, None if the server does not expose it or the
+    /// embedded `CapabilityRegistry` does not know it
+    pub fn capability_info(&self, name: &str) -> Option<&'static CapabilityInfo> {
+        if !self.has_static_capability(name) && !self.has_virtual_capability(name) {
+            return None;
+        }
+        CapabilityRegistry::embedded().get(name)
+    }
+
+    /// Compares the embedded `CapabilityRegistry` with the capabilities currently exposed by the WM server
+    pub fn check_capability_registry(&self) -> Result<RegistryCheck, WmError> {
+        Ok(CapabilityRegistry::embedded().check(&self.get_info()?))
+    }
+}
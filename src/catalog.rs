@@ -0,0 +1,194 @@
+/// A device found by `DeviceCatalog::search`, with its relevance from 0 (excluded) to 1 (exact match)
+#[derive(Debug, Clone, PartialEq)]
+pub struct CatalogMatch<'a> {
+    pub device: &'a MakeModel,
+    pub score: f64,
+}
+
+// Minimum score of the devices returned by search
+const MIN_SEARCH_SCORE: f64 = 0.5;
+
+/// DeviceCatalog - index of all the devices known by the WM server, searchable by brand, model and marketing name.
+/// All lookups ignore case and punctuation, so "galaxy s9", "Galaxy-S9" and "GALAXY S9" are the same name.
+/// Basic usage:
+/// ```no_run
+/// use wmclient::WmClient;
+/// let client = WmClient::new("http", "localhost", "8080", "").unwrap();
+/// let catalog = client.get_device_catalog().unwrap();
+/// for m in catalog.search("galaxy s9", 5) {
+///     println!("{} {} ({:.2})", m.device.brand_name, m.device.model_name, m.score);
+/// }
+/// let devices = catalog.find_by_marketing_name("Galaxy S9");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DeviceCatalog {
+    _devices: Vec<MakeModel>,
+    // normalized search keys of each device, same order as _devices
+    _keys: Vec<CatalogKeys>,
+    _brand_names: NameIndex,
+}
+
+#[derive(Debug, Clone, Default)]
+struct CatalogKeys {
+    brand: String,
+    model: String,
+    marketing: String,
+    // names a device may be searched by: model, marketing name, and both prefixed by the brand
+    names: Vec<String>,
+}
+
+impl DeviceCatalog {
+    /// Creates a catalog with the given devices, sorted by brand, model and marketing name
+    pub fn new(mut devices: Vec<MakeModel>) -> DeviceCatalog {
+        devices.sort();
+        devices.dedup();
+        let keys = devices.iter().map(|d| {
+            let brand = _normalize_name(&d.brand_name);
+            let model = _normalize_name(&d.model_name);
+            let marketing = _normalize_name(d.marketing_name.as_deref().unwrap_or(""));
+            let mut names = vec![model.clone(), format!("{} {}", brand, model)];
+            if !marketing.is_empty() {
+                names.push(marketing.clone());
+                names.push(format!("{} {}", brand, marketing));
+            }
+            CatalogKeys { brand, model, marketing, names }
+        }).collect();
+        let brand_names = NameIndex::new(devices.iter().map(|d| d.brand_name.as_str()));
+        DeviceCatalog { _devices: devices, _keys: keys, _brand_names: brand_names }
+    }
+
+    /// All the devices of the catalog
+    pub fn devices(&self) -> &[MakeModel] {
+        &self._devices
+    }
+
+    /// Number of devices in the catalog
+    pub fn len(&self) -> usize {
+        self._devices.len()
+    }
+
+    /// True if the catalog has no devices
+    pub fn is_empty(&self) -> bool {
+        self._devices.is_empty()
+    }
+
+    /// Sorted names of all the brands
+    pub fn brands(&self) -> Vec<&str> {
+        let mut brands: Vec<&str> = self._devices.iter().map(|d| d.brand_name.as_str()).collect();
+        brands.dedup();
+        brands
+    }
+
+    /// Returns the brand name, as written in WURFL, that matches `brand_name` ignoring case and punctuation,
+    /// or through a known alias (e.g. "Samsung Electronics")
+    pub fn find_brand(&self, brand_name: &str) -> Option<&str> {
+        self._brand_names.find(brand_name, &BRAND_NAME_ALIASES)
+    }
+
+    /// Devices of the given brand, matched as done by `find_brand`
+    pub fn devices_for_brand(&self, brand_name: &str) -> Vec<&MakeModel> {
+        match self.find_brand(brand_name) {
+            Some(brand) => self._devices.iter().filter(|d| d.brand_name == brand).collect(),
+            None => vec![],
+        }
+    }
+
+    /// Devices with the given model name
+    pub fn find_by_model(&self, model_name: &str) -> Vec<&MakeModel> {
+        let model = _normalize_name(model_name);
+        self._filter(|k| k.model == model)
+    }
+
+    /// Devices sold with the given marketing name, for example "Galaxy S9" returns the SM-G960* Samsung models
+    pub fn find_by_marketing_name(&self, marketing_name: &str) -> Vec<&MakeModel> {
+        let marketing = _normalize_name(marketing_name);
+        if marketing.is_empty() {
+            return vec![];
+        }
+        self._filter(|k| k.marketing == marketing)
+    }
+
+    /// Devices whose brand, model or marketing name (optionally preceded by the brand) starts with `prefix`
+    pub fn search_prefix(&self, prefix: &str) -> Vec<&MakeModel> {
+        let prefix = _normalize_name(prefix);
+        if prefix.is_empty() {
+            return vec![];
+        }
+        self._filter(|k| k.brand.starts_with(&prefix) || k.names.iter().any(|n| n.starts_with(&prefix)))
+    }
+
+    /// Searches devices by brand, model and marketing name, tolerating partial words and typos.
+    /// Returns at most `limit` matches, the most relevant first
+    pub fn search(&self, query: &str, limit: usize) -> Vec<CatalogMatch<'_>> {
+        let query = _normalize_name(query);
+        if query.is_empty() {
+            return vec![];
+        }
+        let query_tokens: Vec<&str> = query.split(' ').collect();
+        let mut matches: Vec<CatalogMatch> = self._devices.iter().zip(&self._keys).filter_map(|(device, keys)| {
+            let score = keys.names.iter().map(|name| _name_score(&query, &query_tokens, name)).fold(0.0, f64::max);
+            if score >= MIN_SEARCH_SCORE {
+                Some(CatalogMatch { device, score })
+            } else {
+                None
+            }
+        }).collect();
+        // sort is stable: devices with the same score stay sorted by brand and model
+        matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+        matches.truncate(limit);
+        matches
+    }
+
+    fn _filter<F: Fn(&CatalogKeys) -> bool>(&self, predicate: F) -> Vec<&MakeModel> {
+        self._devices.iter().zip(&self._keys).filter(|(_, k)| predicate(k)).map(|(d, _)| d).collect()
+    }
+}
+
+impl WmClient {
+    /// Returns a `DeviceCatalog` of all the devices known by the WM server.
+    /// It uses the same data downloaded for `get_all_device_makes` and `get_all_devices_for_make`
+    pub fn get_device_catalog(&self) -> Result<DeviceCatalog, WmError> {
+        Ok(DeviceCatalog::new(self.get_make_models()?.iter().cloned().collect()))
+    }
+}
+
+// Lower case words made of letters, digits and '+' (as in "Galaxy S9+"), separated by a single space
+fn _normalize_name(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric() && c != '+')
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+// Relevance of a normalized name for a normalized query: 1 for the same name, otherwise the average match of the
+// query words, lowered when the name has more words than the query
+fn _name_score(query: &str, query_tokens: &[&str], name: &str) -> f64 {
+    if query == name {
+        return 1.0;
+    }
+    let name_tokens: Vec<&str> = name.split(' ').collect();
+    let mut total = 0.0;
+    for query_token in query_tokens {
+        let token_score = name_tokens.iter().map(|t| _token_score(query_token, t)).fold(0.0, f64::max);
+        if token_score == 0.0 {
+            return 0.0;
+        }
+        total += token_score;
+    }
+    let coverage = query_tokens.len() as f64 / std::cmp::max(query_tokens.len(), name_tokens.len()) as f64;
+    0.9 * (total / query_tokens.len() as f64) * (0.5 + 0.5 * coverage)
+}
+
+// Match of a query word against a name word: whole word, word prefix, or word with a typo
+fn _token_score(query_token: &str, name_token: &str) -> f64 {
+    if query_token == name_token {
+        1.0
+    } else if name_token.starts_with(query_token) {
+        0.8
+    } else if query_token.chars().count() >= 4 && _edit_distance(query_token, name_token) <= 1 {
+        0.6
+    } else {
+        0.0
+    }
+}
@@ -0,0 +1,92 @@
+// User-Agent Client Hints request headers (https://wicg.github.io/ua-client-hints/)
+pub const SEC_CH_UA: &str = "Sec-CH-UA";
+pub const SEC_CH_UA_MOBILE: &str = "Sec-CH-UA-Mobile";
+pub const SEC_CH_UA_PLATFORM: &str = "Sec-CH-UA-Platform";
+pub const SEC_CH_UA_PLATFORM_VERSION: &str = "Sec-CH-UA-Platform-Version";
+pub const SEC_CH_UA_MODEL: &str = "Sec-CH-UA-Model";
+pub const SEC_CH_UA_FULL_VERSION_LIST: &str = "Sec-CH-UA-Full-Version-List";
+
+/// Client hints that `lookup_headers` forwards to the WM server, even when they are not listed in the
+/// server `important_headers`. Chromium based browsers send a reduced User-Agent string, so these headers are
+/// needed to detect the device model and the exact OS and browser versions.
+pub const CLIENT_HINTS_HEADERS: &[&str] = &[
+    SEC_CH_UA,
+    SEC_CH_UA_MOBILE,
+    SEC_CH_UA_PLATFORM,
+    SEC_CH_UA_PLATFORM_VERSION,
+    SEC_CH_UA_MODEL,
+    SEC_CH_UA_FULL_VERSION_LIST,
+];
+
+// High entropy hints are not sent by browsers unless the server asks for them
+const HIGH_ENTROPY_HINTS: &[&str] = &[SEC_CH_UA_PLATFORM_VERSION, SEC_CH_UA_MODEL, SEC_CH_UA_FULL_VERSION_LIST];
+
+/// ClientHints - generates the `Accept-CH` and `Critical-CH` response headers that ask browsers to send the
+/// client hints used for device detection.
+/// Basic usage:
+/// ```
+/// use wmclient::ClientHints;
+/// let hints = ClientHints::new();
+/// for (name, value) in hints.response_headers() {
+///     // add name and value to the HTTP response...
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ClientHints {
+    accept: Vec<String>,
+    critical: Vec<String>,
+}
+
+impl ClientHints {
+    /// Creates a ClientHints that accepts all the hints in `CLIENT_HINTS_HEADERS`, marking the high entropy ones
+    /// (`Sec-CH-UA-Platform-Version`, `Sec-CH-UA-Model`, `Sec-CH-UA-Full-Version-List`) as critical, so that
+    /// browsers retry the first request including them.
+    pub fn new() -> ClientHints {
+        ClientHints {
+            accept: CLIENT_HINTS_HEADERS.iter().map(|h| h.to_string()).collect(),
+            critical: HIGH_ENTROPY_HINTS.iter().map(|h| h.to_string()).collect(),
+        }
+    }
+
+    /// Creates a ClientHints with the given accepted and critical hints. Critical hints are added to the accepted ones
+    /// if missing.
+    pub fn with_hints(accept: Vec<&str>, critical: Vec<&str>) -> ClientHints {
+        let mut hints = ClientHints { accept: vec![], critical: vec![] };
+        for name in accept.into_iter().chain(critical.iter().copied()) {
+            if !hints.accept.iter().any(|h| h.eq_ignore_ascii_case(name)) {
+                hints.accept.push(name.to_string());
+            }
+        }
+        for name in critical {
+            if !hints.critical.iter().any(|h| h.eq_ignore_ascii_case(name)) {
+                hints.critical.push(name.to_string());
+            }
+        }
+        hints
+    }
+
+    /// Value of the `Accept-CH` response header
+    pub fn accept_ch(&self) -> String {
+        self.accept.join(", ")
+    }
+
+    /// Value of the `Critical-CH` response header
+    pub fn critical_ch(&self) -> String {
+        self.critical.join(", ")
+    }
+
+    /// Returns the `Accept-CH` and `Critical-CH` (only if any critical hint is set) response headers as name/value pairs
+    pub fn response_headers(&self) -> Vec<(String, String)> {
+        let mut headers = vec![("Accept-CH".to_string(), self.accept_ch())];
+        if !self.critical.is_empty() {
+            headers.push(("Critical-CH".to_string(), self.critical_ch()));
+        }
+        headers
+    }
+}
+
+impl Default for ClientHints {
+    fn default() -> Self {
+        ClientHints::new()
+    }
+}
@@ -0,0 +1,69 @@
+/// A capability whose value differs between two devices
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapabilityChange {
+    pub name: String,
+    pub old_value: String,
+    pub new_value: String,
+}
+
+/// DeviceDiff - capabilities that differ between two device detections, for example the same User-Agent looked up
+/// on two WM servers or before and after a WURFL data update. Only capabilities are compared: `wurfl_id` being one
+/// of them, a different detected device shows up as a change of `wurfl_id`.
+/// Basic usage:
+/// ```no_run
+/// use wmclient::{DeviceDiff, WmClient};
+/// let old_client = WmClient::new("http", "localhost", "8080", "").unwrap();
+/// let new_client = WmClient::new("http", "localhost", "8081", "").unwrap();
+/// let user_agent = "Mozilla/5.0 (Linux; Android 10; SM-G960F)".to_string();
+/// let diff = DeviceDiff::between(&old_client.lookup_useragent(user_agent.clone()).unwrap(),
+///                                &new_client.lookup_useragent(user_agent).unwrap());
+/// for change in &diff.changed {
+///     println!("{}: {} -> {}", change.name, change.old_value, change.new_value);
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceDiff {
+    /// Capabilities of the new device only, with their values
+    pub added: std::collections::BTreeMap<String, String>,
+    /// Capabilities of the old device only, with their values
+    pub removed: std::collections::BTreeMap<String, String>,
+    /// Capabilities of both devices with different values, by name
+    pub changed: Vec<CapabilityChange>,
+}
+
+impl DeviceDiff {
+    /// Compares the capabilities of the `old` device with the ones of the `new` device
+    pub fn between(old: &JSONDeviceData, new: &JSONDeviceData) -> DeviceDiff {
+        let mut diff = DeviceDiff::default();
+        for (name, old_value) in &old.capabilities {
+            match new.capabilities.get(name) {
+                Some(new_value) if new_value != old_value => diff.changed.push(CapabilityChange {
+                    name: name.clone(),
+                    old_value: old_value.clone(),
+                    new_value: new_value.clone(),
+                }),
+                Some(_) => {}
+                None => {
+                    diff.removed.insert(name.clone(), old_value.clone());
+                }
+            }
+        }
+        for (name, new_value) in &new.capabilities {
+            if !old.capabilities.contains_key(name) {
+                diff.added.insert(name.clone(), new_value.clone());
+            }
+        }
+        diff.changed.sort_by(|a, b| a.name.cmp(&b.name));
+        diff
+    }
+
+    /// True if both devices have the same capabilities with the same values
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// The change of the named capability, if its value differs
+    pub fn change(&self, name: &str) -> Option<&CapabilityChange> {
+        self.changed.iter().find(|change| change.name == name)
+    }
+}
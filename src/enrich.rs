@@ -0,0 +1,279 @@
+/// Format of the access log records read by `LogEnricher`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Combined Log Format, one record per line: the User-Agent is the third quoted field
+    /// (after the request line and the referer). Capabilities are appended to the line as `name="value"` fields
+    Combined,
+    /// One JSON object per line, with the User-Agent in the given field. Capabilities are added as an object in the
+    /// `wurfl` field, at the end of the record: the rest of the line is kept as it is. Records that already have a
+    /// `wurfl` field are skipped
+    JsonLines { ua_field: String },
+    /// CSV with a header record, with the User-Agent in the column with the given name. Capabilities are added as
+    /// new columns, left empty for records whose User-Agent is not valid UTF-8
+    Csv { ua_column: String },
+}
+
+/// Counters of the records processed by `LogEnricher::enrich`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EnrichStats {
+    /// Number of records read
+    pub records: u64,
+    /// Number of records written with the detected device capabilities
+    pub enriched: u64,
+    /// Number of records without a readable User-Agent, written unchanged
+    pub skipped: u64,
+    /// Number of records whose User-Agent lookup returned an error, written unchanged
+    pub errors: u64,
+}
+
+/// LogEnricher - reads web server access logs, detects the device of each record User-Agent and writes the same
+/// records enriched with the device capabilities.
+/// Records are processed one at a time, so logs of any size can be enriched with constant memory. Lookups go through
+/// the client cache: logs usually contain few distinct User-Agents, most of them are served without calling the server.
+/// An unreachable WM server stops the enrichment with an error, any other lookup error leaves the record unchanged.
+/// Basic usage:
+/// ```no_run
+/// use std::fs::File;
+/// use std::io::BufReader;
+/// use wmclient::{LogEnricher, LogFormat, WmClient};
+/// let mut client = WmClient::new("http", "localhost", "8080", "").unwrap();
+/// client.set_cache_size(100000);
+/// let input = BufReader::new(File::open("access.log").unwrap());
+/// let output = File::create("access_enriched.log").unwrap();
+/// let stats = LogEnricher::new(&client, LogFormat::Combined)
+///     .capabilities(vec!["brand_name", "model_name", "form_factor"])
+///     .unwrap()
+///     .enrich(input, output)
+///     .unwrap();
+/// println!("{} records enriched", stats.enriched);
+/// ```
+pub struct LogEnricher<'a> {
+    _client: &'a WmClient,
+    _format: LogFormat,
+    _caps: Option<CapabilitySet>,
+}
+
+impl<'a> LogEnricher<'a> {
+    /// Creates an enricher that adds the capabilities requested by `client` to records in the given format
+    pub fn new(client: &'a WmClient, format: LogFormat) -> LogEnricher<'a> {
+        LogEnricher { _client: client, _format: format, _caps: None }
+    }
+
+    /// Records are enriched with the given capabilities (see `CapabilitySet`) instead of the ones requested by the client.
+    /// Returns a `WmErrorKind::InvalidCapability` error if a name is unknown to the WM server
+    pub fn capabilities(mut self, cap_list: Vec<&str>) -> Result<LogEnricher<'a>, WmError> {
+        self._caps = Some(CapabilitySet::try_new(self._client, cap_list, CapabilityParsing::Strict)?);
+        Ok(self)
+    }
+
+    /// Names of the capabilities added to each record, in output order. `wurfl_id` always comes first
+    pub fn columns(&self) -> Vec<String> {
+        let (static_caps, virtual_caps) = match &self._caps {
+            Some(caps) => (caps.static_caps().to_vec(), caps.virtual_caps().to_vec()),
+            None => (
+                self._client.requested_static_caps.clone().unwrap_or_default(),
+                self._client.requested_virtual_caps.clone().unwrap_or_default(),
+            ),
+        };
+        // nothing requested means all the capabilities
+        let (static_caps, virtual_caps) = if static_caps.is_empty() && virtual_caps.is_empty() {
+            (self._client.static_caps.clone(), self._client.virtual_caps.clone())
+        } else {
+            (static_caps, virtual_caps)
+        };
+        let mut columns = vec!["wurfl_id".to_string()];
+        columns.extend(static_caps.into_iter().chain(virtual_caps).filter(|c| c != "wurfl_id"));
+        columns
+    }
+
+    /// Reads all the records from `input` and writes them, enriched, to `output`
+    pub fn enrich<R: BufRead, W: Write>(&self, input: R, output: W) -> Result<EnrichStats, WmError> {
+        let columns = self.columns();
+        match &self._format {
+            LogFormat::Csv { ua_column } => self._enrich_csv(input, output, ua_column, &columns),
+            _ => self._enrich_lines(input, output, &columns),
+        }
+    }
+
+    // Detects the device of a record; None when the lookup fails with an error that does not stop the enrichment
+    fn _lookup(&self, user_agent: String, stats: &mut EnrichStats) -> Result<Option<JSONDeviceData>, WmError> {
+        let result = match &self._caps {
+            Some(caps) => self._client.lookup_useragent_with(caps, user_agent),
+            None => self._client.lookup_useragent(user_agent),
+        };
+        match result {
+            Ok(device) => {
+                stats.enriched += 1;
+                Ok(Some(device))
+            }
+            Err(err) if err.kind == WmErrorKind::Unreachable => Err(err),
+            Err(_) => {
+                stats.errors += 1;
+                Ok(None)
+            }
+        }
+    }
+
+    fn _enrich_lines<R: BufRead, W: Write>(&self, mut input: R, mut output: W, columns: &[String]) -> Result<EnrichStats, WmError> {
+        let mut stats = EnrichStats::default();
+        let mut buf = vec![];
+        loop {
+            buf.clear();
+            if input.read_until(b'\n', &mut buf).map_err(_io_error)? == 0 {
+                break;
+            }
+            let line = match from_utf8(&buf) {
+                Ok(line) => line.trim_end_matches(['\r', '\n']),
+                Err(_) => {
+                    // not valid UTF-8: the record is copied as is
+                    stats.records += 1;
+                    stats.skipped += 1;
+                    output.write_all(&buf).map_err(_io_error)?;
+                    continue;
+                }
+            };
+            if line.is_empty() {
+                output.write_all(b"\n").map_err(_io_error)?;
+                continue;
+            }
+            stats.records += 1;
+            let enriched = match &self._format {
+                LogFormat::JsonLines { ua_field } => self._enrich_json_line(line, ua_field, columns, &mut stats)?,
+                _ => self._enrich_combined_line(line, columns, &mut stats)?,
+            };
+            writeln!(output, "{}", enriched.as_deref().unwrap_or(line)).map_err(_io_error)?;
+        }
+        output.flush().map_err(_io_error)?;
+        Ok(stats)
+    }
+
+    fn _enrich_combined_line(&self, line: &str, columns: &[String], stats: &mut EnrichStats) -> Result<Option<String>, WmError> {
+        let user_agent = match _combined_log_user_agent(line) {
+            Some(user_agent) => user_agent,
+            None => {
+                stats.skipped += 1;
+                return Ok(None);
+            }
+        };
+        Ok(self._lookup(user_agent, stats)?.map(|device| {
+            let mut enriched = line.to_string();
+            for column in columns {
+                let value = device.capabilities.get(column).map(|v| v.as_str()).unwrap_or("");
+                enriched.push_str(&format!(" {}=\"{}\"", column, value.replace('\\', "\\\\").replace('"', "\\\"")));
+            }
+            enriched
+        }))
+    }
+
+    fn _enrich_json_line(&self, line: &str, ua_field: &str, columns: &[String], stats: &mut EnrichStats) -> Result<Option<String>, WmError> {
+        let record = match serde_json::from_str::<serde_json::Value>(line) {
+            Ok(serde_json::Value::Object(record)) => record,
+            _ => {
+                stats.skipped += 1;
+                return Ok(None);
+            }
+        };
+        // a wurfl field that is already there would end up duplicated
+        let user_agent = match record.get(ua_field).and_then(|v| v.as_str()) {
+            Some(user_agent) if !record.contains_key("wurfl") => user_agent.to_string(),
+            _ => {
+                stats.skipped += 1;
+                return Ok(None);
+            }
+        };
+        match self._lookup(user_agent, stats)? {
+            Some(device) => {
+                // the wurfl object is spliced in before the closing brace, so that the original text (field order,
+                // number formatting, escapes) is kept as it is
+                let mut caps = vec![];
+                for column in columns {
+                    let value = device.capabilities.get(column).map(|v| v.as_str()).unwrap_or("");
+                    caps.push(format!("{}:{}", serde_json::to_string(column)?, serde_json::to_string(value)?));
+                }
+                let line = line.trim_end();
+                let separator = if record.is_empty() { "" } else { "," };
+                Ok(Some(format!("{}{}\"wurfl\":{{{}}}}}", &line[..line.len() - 1], separator, caps.join(","))))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn _enrich_csv<R: BufRead, W: Write>(&self, input: R, output: W, ua_column: &str, columns: &[String]) -> Result<EnrichStats, WmError> {
+        let mut stats = EnrichStats::default();
+        let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(input);
+        let mut writer = csv::WriterBuilder::new().flexible(true).from_writer(output);
+
+        let mut header = reader.byte_headers().map_err(_csv_error)?.clone();
+        let ua_index = match header.iter().position(|h| h == ua_column.as_bytes()) {
+            Some(index) => index,
+            None => return Err(WmError::new(format!("CSV column {} not found", ua_column))),
+        };
+        header.extend(columns);
+        writer.write_record(&header).map_err(_csv_error)?;
+
+        // records are read as bytes: a User-Agent that is not valid UTF-8 only skips its own record
+        let mut record = csv::ByteRecord::new();
+        while reader.read_byte_record(&mut record).map_err(_csv_error)? {
+            stats.records += 1;
+            let device = match record.get(ua_index).map(from_utf8) {
+                Some(Ok(user_agent)) => self._lookup(user_agent.to_string(), &mut stats)?,
+                _ => {
+                    stats.skipped += 1;
+                    None
+                }
+            };
+            // all the rows keep the same columns: failed lookups get empty values
+            let values = columns.iter().map(|column| {
+                device.as_ref().and_then(|d| d.capabilities.get(column)).map(|v| v.as_str()).unwrap_or("")
+            });
+            let mut enriched = record.clone();
+            enriched.extend(values);
+            writer.write_record(&enriched).map_err(_csv_error)?;
+        }
+        writer.flush().map_err(_io_error)?;
+        Ok(stats)
+    }
+}
+
+// Returns the third quoted field of a Combined Log Format line, unescaping \" and \\
+fn _combined_log_user_agent(line: &str) -> Option<String> {
+    let mut fields = vec![];
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c != '"' {
+            continue;
+        }
+        let mut field = String::new();
+        let mut closed = false;
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => {
+                    if let Some(escaped) = chars.next() {
+                        field.push(escaped);
+                    }
+                }
+                '"' => {
+                    closed = true;
+                    break;
+                }
+                _ => field.push(c),
+            }
+        }
+        if !closed {
+            return None;
+        }
+        fields.push(field);
+        if fields.len() == 3 {
+            return fields.pop();
+        }
+    }
+    None
+}
+
+fn _io_error(err: std::io::Error) -> WmError {
+    WmError::new(format!("Unable to enrich log: {}", err))
+}
+
+fn _csv_error(err: csv::Error) -> WmError {
+    WmError::new(format!("Unable to enrich CSV log: {}", err))
+}
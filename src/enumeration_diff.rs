@@ -0,0 +1,116 @@
+/// Values added and removed between two versions of a list, sorted
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Changes<T> {
+    pub added: Vec<T>,
+    pub removed: Vec<T>,
+}
+
+impl<T: Ord + Clone> Changes<T> {
+    // Compares two sets of values
+    fn between(old: &std::collections::BTreeSet<T>, new: &std::collections::BTreeSet<T>) -> Changes<T> {
+        Changes {
+            added: new.difference(old).cloned().collect(),
+            removed: old.difference(new).cloned().collect(),
+        }
+    }
+}
+
+impl<T> Changes<T> {
+    /// True if nothing was added or removed
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// A device model of a brand
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct BrandModel {
+    pub brand_name: String,
+    pub model_name: String,
+}
+
+/// A version of an OS
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct OsNameVersion {
+    pub device_os: String,
+    pub device_os_version: String,
+}
+
+/// EnumerationDiff - what changed between two enumeration snapshots, for example before and after a WURFL data update.
+/// Marketing names are compared only for models found in both snapshots, and OS versions only for OSes found in both:
+/// the ones of added or removed models and OSes are not repeated.
+/// Basic usage:
+/// ```no_run
+/// use std::fs::File;
+/// use wmclient::{EnumerationSnapshot, WmClient};
+/// let client = WmClient::new("http", "localhost", "8080", "").unwrap();
+/// let old = EnumerationSnapshot::read(File::open("enumerations.json").unwrap()).unwrap();
+/// let diff = client.diff_enumerations(&old).unwrap();
+/// for brand in &diff.brands.added {
+///     println!("New brand: {}", brand);
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnumerationDiff {
+    /// `wurfl_info` of the old snapshot
+    pub old_wurfl_info: String,
+    /// `wurfl_info` of the new snapshot
+    pub new_wurfl_info: String,
+    pub brands: Changes<String>,
+    pub models: Changes<BrandModel>,
+    pub marketing_names: Changes<MakeModel>,
+    pub oses: Changes<String>,
+    pub os_versions: Changes<OsNameVersion>,
+}
+
+impl EnumerationDiff {
+    /// Compares the `old` snapshot with the `new` one
+    pub fn between(old: &EnumerationSnapshot, new: &EnumerationSnapshot) -> EnumerationDiff {
+        let brands = |s: &EnumerationSnapshot| s.make_models.iter().map(|m| m.brand_name.clone()).collect();
+        let models = |s: &EnumerationSnapshot| s.make_models.iter().map(|m| BrandModel {
+            brand_name: m.brand_name.clone(),
+            model_name: m.model_name.clone(),
+        }).collect::<std::collections::BTreeSet<BrandModel>>();
+        let (old_models, new_models) = (models(old), models(new));
+        let marketing_names = |s: &EnumerationSnapshot| s.make_models.iter().filter(|m| {
+            let model = BrandModel { brand_name: m.brand_name.clone(), model_name: m.model_name.clone() };
+            old_models.contains(&model) && new_models.contains(&model)
+        }).filter_map(|m| match &m.marketing_name {
+            Some(name) if !name.is_empty() => Some(m.clone()),
+            _ => None,
+        }).collect();
+
+        let oses = |s: &EnumerationSnapshot| s.os_versions.keys().cloned().collect::<std::collections::BTreeSet<String>>();
+        let (old_oses, new_oses) = (oses(old), oses(new));
+        let os_versions = |s: &EnumerationSnapshot| s.os_versions.iter()
+            .filter(|(os, _)| old_oses.contains(*os) && new_oses.contains(*os))
+            .flat_map(|(os, versions)| versions.iter().filter(|v| !v.is_empty()).map(move |v| OsNameVersion {
+                device_os: os.clone(),
+                device_os_version: v.clone(),
+            }))
+            .collect();
+
+        EnumerationDiff {
+            old_wurfl_info: old.info.wurfl_info.clone(),
+            new_wurfl_info: new.info.wurfl_info.clone(),
+            brands: Changes::between(&brands(old), &brands(new)),
+            models: Changes::between(&old_models, &new_models),
+            marketing_names: Changes::between(&marketing_names(old), &marketing_names(new)),
+            oses: Changes::between(&old_oses, &new_oses),
+            os_versions: Changes::between(&os_versions(old), &os_versions(new)),
+        }
+    }
+
+    /// True if the two snapshots have the same enumeration data
+    pub fn is_empty(&self) -> bool {
+        self.brands.is_empty() && self.models.is_empty() && self.marketing_names.is_empty()
+            && self.oses.is_empty() && self.os_versions.is_empty()
+    }
+}
+
+impl WmClient {
+    /// Compares the `old` snapshot with the current enumeration data of the WM server
+    pub fn diff_enumerations(&self, old: &EnumerationSnapshot) -> Result<EnumerationDiff, WmError> {
+        Ok(EnumerationDiff::between(old, &self.get_enumeration_snapshot()?))
+    }
+}
@@ -0,0 +1,171 @@
+// Makes and models data, built from a single download of /v2/alldevices/json
+struct MakesSnapshot {
+    // records as received from the server
+    make_models: Arc<Vec<MakeModel>>,
+    // list of device manufacturers
+    device_makes: Vec<String>,
+    // model and marketing names of each manufacturer
+    device_makes_map: HashMap<String, Vec<JSONModelMktName>>,
+    // manufacturers, for normalized lookups
+    make_names: NameIndex,
+    loaded_at: std::time::Instant,
+}
+
+// OS data, built from a single download of /v2/alldeviceosversions/json
+struct OsSnapshot {
+    // list of all device OSes
+    device_oses: Vec<String>,
+    // versions of each OS
+    os_versions_map: HashMap<String, Vec<String>>,
+    // OSes, for normalized lookups
+    os_names: NameIndex,
+    loaded_at: std::time::Instant,
+}
+
+impl WmClient {
+    /// Sets the maximum age of enumeration data (makes, models, OSes and versions): once expired it is downloaded
+    /// again on next use, by a single thread while the others keep using the expired data. If the download fails,
+    /// the expired data keeps being used until a download succeeds.
+    /// `None` (the default) keeps the data until `refresh_enumerations` or `clear_caches` are called, or the
+    /// WM server reloads its WURFL data.
+    pub fn set_enumerations_ttl(&mut self, ttl: Option<Duration>) {
+        self._enumerations_ttl = ttl;
+    }
+
+    /// Downloads the enumeration data (makes, models, OSes and versions) again and replaces the current one.
+    /// Data is replaced only when both downloads succeed; concurrent calls to the enumeration methods keep
+    /// returning the previous data until then.
+    pub fn refresh_enumerations(&self) -> Result<(), WmError> {
+        let makes = self._download_makes()?;
+        let oses = self._download_oses()?;
+        self._swap_makes(Some(Arc::new(makes)));
+        self._swap_oses(Some(Arc::new(oses)));
+        Ok(())
+    }
+
+    fn _makes_snapshot(&self) -> Result<Arc<MakesSnapshot>, WmError> {
+        self._snapshot(&self._makes, &self._makes_refresh, |makes| makes.loaded_at, || self._download_makes(), "device makes")
+    }
+
+    fn _os_snapshot(&self) -> Result<Arc<OsSnapshot>, WmError> {
+        self._snapshot(&self._oses, &self._oses_refresh, |oses| oses.loaded_at, || self._download_oses(), "device OS")
+    }
+
+    // Returns the current snapshot in slot, downloading it if missing or expired. Downloads are done by one
+    // thread at a time: while expired data is downloaded again, the other threads get the expired snapshot.
+    fn _snapshot<T>(&self, slot: &RwLock<Option<Arc<T>>>, refresh: &Mutex<()>, loaded_at: fn(&T) -> std::time::Instant,
+                    download: impl Fn() -> Result<T, WmError>, data_name: &str) -> Result<Arc<T>, WmError> {
+        let current = self._read_snapshot(slot, data_name)?;
+        let _refresh_guard = match &current {
+            Some(snapshot) if !self._is_expired(loaded_at(snapshot)) => return Ok(snapshot.clone()),
+            Some(snapshot) => match refresh.try_lock() {
+                Ok(guard) => guard,
+                Err(std::sync::TryLockError::WouldBlock) => return Ok(snapshot.clone()),
+                Err(std::sync::TryLockError::Poisoned(err)) => err.into_inner(),
+            },
+            // without data there is nothing to serve meanwhile: wait for the download in progress, if any
+            None => refresh.lock().unwrap_or_else(|err| err.into_inner()),
+        };
+
+        // another thread may have downloaded the data while this one was waiting
+        let current = self._read_snapshot(slot, data_name)?;
+        if let Some(snapshot) = &current {
+            if !self._is_expired(loaded_at(snapshot)) {
+                return Ok(snapshot.clone());
+            }
+        }
+        match download() {
+            Ok(data) => {
+                let snapshot = Arc::new(data);
+                if let Ok(mut guard) = slot.write() {
+                    *guard = Some(snapshot.clone());
+                }
+                Ok(snapshot)
+            }
+            // expired data is better than none
+            Err(wm_err) => current.ok_or(wm_err),
+        }
+    }
+
+    fn _read_snapshot<T>(&self, slot: &RwLock<Option<Arc<T>>>, data_name: &str) -> Result<Option<Arc<T>>, WmError> {
+        match slot.read() {
+            Ok(snapshot) => Ok(snapshot.clone()),
+            Err(err) => Err(WmError::new(format!("Cannot download {} data: {}", data_name, err))),
+        }
+    }
+
+    fn _is_expired(&self, loaded_at: std::time::Instant) -> bool {
+        match self._enumerations_ttl {
+            Some(ttl) => loaded_at.elapsed() >= ttl,
+            None => false,
+        }
+    }
+
+    fn _swap_makes(&self, snapshot: Option<Arc<MakesSnapshot>>) {
+        if let Ok(mut makes) = self._makes.write() {
+            *makes = snapshot;
+        }
+    }
+
+    fn _swap_oses(&self, snapshot: Option<Arc<OsSnapshot>>) {
+        if let Ok(mut oses) = self._oses.write() {
+            *oses = snapshot;
+        }
+    }
+
+    fn _clear_enumerations(&self) {
+        self._swap_makes(None);
+        self._swap_oses(None);
+    }
+
+    fn _download_makes(&self) -> Result<MakesSnapshot, WmError> {
+        let res_string = self._http_get("/v2/alldevices/json")?;
+        let make_models: Vec<MakeModel> = match serde_json::from_str(res_string.as_str()) {
+            Ok(models) => models,
+            Err(err) => return Err(WmError::new(format!("Could not parse device makes data {} ", err))),
+        };
+        Ok(_build_makes_snapshot(make_models))
+    }
+
+    fn _download_oses(&self) -> Result<OsSnapshot, WmError> {
+        let os_vers_str = self._http_get("/v2/alldeviceosversions/json")?;
+        // this is a vector holding pairs of os name ("Android") and version ("10.0")
+        let os_version_pairs: Vec<JSONDeviceOsVersions> = match serde_json::from_str(os_vers_str.as_str()) {
+            Ok(pairs) => pairs,
+            Err(err) => return Err(WmError::new(format!("Could not parse device OS data {} ", err))),
+        };
+        Ok(_build_os_snapshot(os_version_pairs))
+    }
+}
+
+fn _build_makes_snapshot(make_models: Vec<MakeModel>) -> MakesSnapshot {
+    let mut device_makes_map: HashMap<String, Vec<JSONModelMktName>> = HashMap::new();
+    for make_model in &make_models {
+        let md_mk_name = JSONModelMktName {
+            model_name: make_model.model_name.to_string(),
+            marketing_name: make_model.marketing_name.clone().unwrap_or_default(),
+        };
+        device_makes_map.entry(make_model.brand_name.clone()).or_default().push(md_mk_name);
+    }
+    MakesSnapshot {
+        make_models: Arc::new(make_models),
+        device_makes: device_makes_map.keys().cloned().collect(),
+        make_names: NameIndex::new(device_makes_map.keys().map(|m| m.as_str())),
+        device_makes_map,
+        loaded_at: std::time::Instant::now(),
+    }
+}
+
+fn _build_os_snapshot(os_version_pairs: Vec<JSONDeviceOsVersions>) -> OsSnapshot {
+    // Version numbers are guaranteed to be unique for each OS name.
+    let mut os_versions_map: HashMap<String, Vec<String>> = HashMap::new();
+    for ov_item in os_version_pairs {
+        os_versions_map.entry(ov_item.device_os).or_default().push(ov_item.device_os_version);
+    }
+    OsSnapshot {
+        device_oses: os_versions_map.keys().cloned().collect(),
+        os_names: NameIndex::new(os_versions_map.keys().map(|o| o.as_str())),
+        os_versions_map,
+        loaded_at: std::time::Instant::now(),
+    }
+}
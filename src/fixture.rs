@@ -0,0 +1,175 @@
+/// A single request/response exchange between the client and the WM server, as stored in a fixture file.
+/// Fixture files contain one JSON serialized exchange per line.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FixtureExchange {
+    pub method: String,
+    pub path: String,
+    pub request: Option<serde_json::Value>,
+    pub response: String,
+}
+
+/// RecordingTransport - wraps another transport and appends every exchange with the WM server to a fixture file,
+/// one JSON line per exchange. The fixture can later be served by a `ReplayTransport`.
+pub struct RecordingTransport {
+    _inner: Box<dyn Transport>,
+    _path: String,
+    _file: Mutex<File>,
+}
+
+impl RecordingTransport {
+    /// Creates a recording transport that sends requests through `inner` and writes them to the fixture file at `path`.
+    /// The file is truncated if it already exists.
+    pub fn create(inner: Box<dyn Transport>, path: &str) -> Result<RecordingTransport, WmError> {
+        match File::create(path) {
+            Ok(file) => Ok(RecordingTransport { _inner: inner, _path: path.to_string(), _file: Mutex::new(file) }),
+            Err(err) => Err(WmError::new(format!("Unable to create fixture file {}: {}", path, err))),
+        }
+    }
+
+    fn _record(&self, method: &str, url: &str, request: Option<&str>, response: &str) -> Result<(), WmError> {
+        let request_value = match request {
+            Some(body) => Some(serde_json::from_str::<serde_json::Value>(body)?),
+            None => None,
+        };
+        let exchange = FixtureExchange {
+            method: method.to_string(),
+            path: _endpoint_path(url).to_string(),
+            request: request_value,
+            response: response.to_string(),
+        };
+        let mut line = serde_json::to_string(&exchange)?;
+        line.push('\n');
+
+        let mut file = match self._file.lock() {
+            Ok(file) => file,
+            Err(err) => return Err(WmError::new(format!("Unable to write fixture file {}: {}", self._path, err))),
+        };
+        // each exchange is written as soon as it happens, so that an interrupted run still leaves a usable fixture
+        if let Err(err) = file.write_all(line.as_bytes()) {
+            return Err(WmError::new(format!("Unable to write fixture file {}: {}", self._path, err)));
+        }
+        Ok(())
+    }
+}
+
+impl Transport for RecordingTransport {
+    fn get(&self, url: &str) -> Result<String, WmError> {
+        let response = self._inner.get(url)?;
+        self._record("GET", url, None, response.as_str())?;
+        Ok(response)
+    }
+
+    fn post(&self, url: &str, body: &str) -> Result<String, WmError> {
+        let response = self._inner.post(url, body)?;
+        self._record("POST", url, Some(body), response.as_str())?;
+        Ok(response)
+    }
+
+    fn set_timeouts(&mut self, conn_timeout: u64, rw_timeout: u64) {
+        self._inner.set_timeouts(conn_timeout, rw_timeout);
+    }
+}
+
+/// ReplayTransport - serves responses previously recorded by a `RecordingTransport`, without any network access.
+/// Requests are matched on method, endpoint path and JSON body; a request that has not been recorded returns a WmError.
+pub struct ReplayTransport {
+    _exchanges: HashMap<String, String>,
+}
+
+impl ReplayTransport {
+    /// Loads the fixture file at `path`
+    pub fn open(path: &str) -> Result<ReplayTransport, WmError> {
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(err) => return Err(WmError::new(format!("Unable to open fixture file {}: {}", path, err))),
+        };
+
+        let mut exchanges = HashMap::new();
+        for (index, line_res) in BufReader::new(file).lines().enumerate() {
+            let line = match line_res {
+                Ok(line) => line,
+                Err(err) => return Err(WmError::new(format!("Unable to read fixture file {}: {}", path, err))),
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            let exchange: FixtureExchange = match serde_json::from_str(line.as_str()) {
+                Ok(exchange) => exchange,
+                Err(err) => return Err(WmError::new(format!("Invalid exchange at line {} of fixture file {}: {}", index + 1, path, err))),
+            };
+            // if the same request has been recorded more than once, the last response wins
+            let key = _fixture_key(exchange.method.as_str(), exchange.path.as_str(), exchange.request.as_ref());
+            exchanges.insert(key, exchange.response);
+        }
+        Ok(ReplayTransport { _exchanges: exchanges })
+    }
+
+    fn _response(&self, method: &str, url: &str, request: Option<&serde_json::Value>) -> Result<String, WmError> {
+        let path = _endpoint_path(url);
+        let key = _fixture_key(method, path, request);
+        match self._exchanges.get(&key) {
+            Some(response) => Ok(response.to_string()),
+            None => Err(WmError::new(format!("No recorded response in fixture for {} {}", method, path))),
+        }
+    }
+}
+
+impl Transport for ReplayTransport {
+    fn get(&self, url: &str) -> Result<String, WmError> {
+        self._response("GET", url, None)
+    }
+
+    fn post(&self, url: &str, body: &str) -> Result<String, WmError> {
+        let request = serde_json::from_str::<serde_json::Value>(body)?;
+        self._response("POST", url, Some(&request))
+    }
+}
+
+// Fixtures store the endpoint path only (ie: "/v2/getinfo/json"), so that they do not depend on the server address.
+fn _endpoint_path(url: &str) -> &str {
+    match url.find("/v2/") {
+        Some(index) => &url[index..],
+        None => url,
+    }
+}
+
+// Builds the key used to match a request against the recorded ones. Object keys are sorted, so that
+// requests holding the same headers in a different order match the same exchange.
+fn _fixture_key(method: &str, path: &str, request: Option<&serde_json::Value>) -> String {
+    let mut key = format!("{} {}", method, path);
+    if let Some(value) = request {
+        key.push(' ');
+        _write_canonical_json(value, &mut key);
+    }
+    key
+}
+
+fn _write_canonical_json(value: &serde_json::Value, out: &mut String) {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            out.push('{');
+            for (i, k) in keys.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(serde_json::Value::String(k.to_string()).to_string().as_str());
+                out.push(':');
+                _write_canonical_json(&map[k.as_str()], out);
+            }
+            out.push('}');
+        }
+        serde_json::Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                _write_canonical_json(item, out);
+            }
+            out.push(']');
+        }
+        other => out.push_str(other.to_string().as_str()),
+    }
+}
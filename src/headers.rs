@@ -0,0 +1,74 @@
+/// How `lookup_headers` handles header values that are not valid UTF-8
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeaderDecoding {
+    /// Invalid byte sequences are replaced with U+FFFD REPLACEMENT CHARACTER
+    Lossy,
+    /// Header values that are not valid UTF-8 are not sent to the server
+    Skip,
+    /// The lookup fails with a WmError of kind `WmErrorKind::InvalidHeader`
+    Strict,
+}
+
+/// How `lookup_headers` handles headers over the `HeaderPolicy` limits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeaderLimitAction {
+    /// Oversized values and headers beyond the count limit are not sent to the server
+    Ignore,
+    /// The lookup fails with a WmError of kind `WmErrorKind::InvalidHeader`
+    Reject,
+}
+
+/// HeaderPolicy - rules applied by `lookup_headers` to the incoming request headers, which usually come
+/// straight from the internet. Only the headers sent to the server (see `lookup_headers`) are decoded and counted:
+/// values longer than `max_value_length` bytes and the ones read after the first `max_header_count` are ignored,
+/// or make the lookup fail if `over_limit` is `HeaderLimitAction::Reject`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeaderPolicy {
+    pub decoding: HeaderDecoding,
+    pub max_value_length: usize,
+    pub max_header_count: usize,
+    pub over_limit: HeaderLimitAction,
+}
+
+impl Default for HeaderPolicy {
+    /// Lossy decoding, values up to 8 KB, up to 100 headers, values over the limits ignored
+    fn default() -> Self {
+        HeaderPolicy {
+            decoding: HeaderDecoding::Lossy,
+            max_value_length: DEFAULT_MAX_HEADER_VALUE_LENGTH,
+            max_header_count: DEFAULT_MAX_HEADER_COUNT,
+            over_limit: HeaderLimitAction::Ignore,
+        }
+    }
+}
+
+impl HeaderPolicy {
+    // Returns the decoded header value, None if the value must be skipped, or an error if the policy rejects it.
+    // `count` is the number of values already read
+    fn _decode(&self, name: &str, value: &[u8], count: usize) -> Result<Option<String>, WmError> {
+        if count >= self.max_header_count {
+            if self.over_limit == HeaderLimitAction::Reject {
+                return Err(WmError::with_kind(WmErrorKind::InvalidHeader,
+                                              format!("Request has more than {} headers", self.max_header_count)));
+            }
+            return Ok(None);
+        }
+        if value.len() > self.max_value_length {
+            if self.over_limit == HeaderLimitAction::Reject {
+                return Err(WmError::with_kind(WmErrorKind::InvalidHeader,
+                                              format!("Value of header {} exceeds {} bytes", name, self.max_value_length)));
+            }
+            return Ok(None);
+        }
+
+        match from_utf8(value) {
+            Ok(str_value) => Ok(Some(str_value.to_string())),
+            Err(err) => match self.decoding {
+                HeaderDecoding::Lossy => Ok(Some(String::from_utf8_lossy(value).to_string())),
+                HeaderDecoding::Skip => Ok(None),
+                HeaderDecoding::Strict => Err(WmError::with_kind(WmErrorKind::InvalidHeader,
+                                                                 format!("Value of header {} is not valid UTF-8: {}", name, err))),
+            },
+        }
+    }
+}
@@ -0,0 +1,81 @@
+impl WmClient {
+    /// lookup_header_map - Performs a device detection based on an `http::HeaderMap`, as the one of requests received by
+    /// hyper, axum and other `http` crate based frameworks.
+    /// Header names are matched through `HeaderName` case insensitivity and all the values of repeated headers are folded,
+    /// in order, into a single comma separated value (RFC 9110, section 5.3).
+    /// Values are read according to the client `HeaderPolicy`.
+    pub fn lookup_header_map(&self, header_map: &http::HeaderMap) -> Result<JSONDeviceData, WmError> {
+        self._lookup_header_map(header_map, None, self._cache.as_ref())
+    }
+
+    /// lookup_header_map_with - Same as `lookup_header_map`, returning the capabilities in `caps` instead of the client requested ones
+    pub fn lookup_header_map_with(&self, caps: &CapabilitySet, header_map: &http::HeaderMap) -> Result<JSONDeviceData, WmError> {
+        self._lookup_header_map(header_map, Some(caps), self._cache.as_ref())
+    }
+
+    fn _lookup_header_map(&self, header_map: &http::HeaderMap, caps: Option<&CapabilitySet>, cache: Option<&Cache>) -> Result<JSONDeviceData, WmError> {
+        let policy = self._header_policy;
+        let mut headers: HashMap<String, String> = HashMap::new();
+        let mut count = 0;
+        for h_name in self._lookup_header_names() {
+            let mut h_value = String::new();
+            for value in header_map.get_all(h_name) {
+                let decoded = policy._decode(h_name, value.as_bytes(), count)?;
+                count += 1;
+                match decoded {
+                    Some(decoded) if !decoded.is_empty() => {
+                        if !h_value.is_empty() {
+                            h_value.push_str(", ");
+                        }
+                        h_value.push_str(&decoded);
+                    }
+                    _ => {}
+                }
+            }
+            if !h_value.is_empty() {
+                headers.insert(h_name.to_string(), h_value);
+            }
+        }
+        self._lookup_collected_headers(headers, caps, cache)
+    }
+
+    /// lookup_request - Performs a device detection based on the headers of an `http::Request`. See `lookup_header_map`.
+    pub fn lookup_request<B>(&self, req: &http::Request<B>) -> Result<JSONDeviceData, WmError> {
+        self.lookup_header_map(req.headers())
+    }
+
+    /// lookup_request_with - Same as `lookup_request`, returning the capabilities in `caps` instead of the client requested ones
+    pub fn lookup_request_with<B>(&self, caps: &CapabilitySet, req: &http::Request<B>) -> Result<JSONDeviceData, WmError> {
+        self.lookup_header_map_with(caps, req.headers())
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncWmClient {
+    /// Async version of `WmClient::lookup_header_map`. The header map is copied before the lookup is started.
+    pub fn lookup_header_map(&self, header_map: &http::HeaderMap) -> impl std::future::Future<Output=Result<JSONDeviceData, WmError>> + Send {
+        self._lookup_header_map(header_map, None)
+    }
+
+    /// Async version of `WmClient::lookup_request`. The request headers are copied before the lookup is started,
+    /// so the request does not have to outlive the returned future.
+    pub fn lookup_request<B>(&self, req: &http::Request<B>) -> impl std::future::Future<Output=Result<JSONDeviceData, WmError>> + Send {
+        self.lookup_header_map(req.headers())
+    }
+
+    /// Async version of `WmClient::lookup_header_map_with`
+    pub fn lookup_header_map_with(&self, caps: &CapabilitySet, header_map: &http::HeaderMap) -> impl std::future::Future<Output=Result<JSONDeviceData, WmError>> + Send {
+        self._lookup_header_map(header_map, Some(caps))
+    }
+
+    /// Async version of `WmClient::lookup_request_with`
+    pub fn lookup_request_with<B>(&self, caps: &CapabilitySet, req: &http::Request<B>) -> impl std::future::Future<Output=Result<JSONDeviceData, WmError>> + Send {
+        self.lookup_header_map_with(caps, req.headers())
+    }
+
+    fn _lookup_header_map(&self, header_map: &http::HeaderMap, caps: Option<&CapabilitySet>) -> impl std::future::Future<Output=Result<JSONDeviceData, WmError>> + Send {
+        let (caps, header_map) = (caps.cloned(), header_map.clone());
+        let client = self._client.clone();
+        AsyncWmClient::_spawn(move || client._lookup_header_map(&header_map, caps.as_ref(), client._cache.as_ref()))
+    }
+}
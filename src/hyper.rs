@@ -0,0 +1,40 @@
+/// WmHyperService - wraps a hyper 1.x service, detecting the device of every incoming request and attaching it
+/// to the request extensions as a `DetectedDevice` before calling the wrapped service. When detection fails the
+/// request reaches the wrapped service without a `DetectedDevice` extension.
+/// Basic usage:
+/// ```ignore
+/// let service = WmHyperService::new(async_client, service_fn(handler));
+/// http1::Builder::new().serve_connection(TokioIo::new(stream), service).await
+/// ```
+#[derive(Clone)]
+pub struct WmHyperService<S> {
+    _client: AsyncWmClient,
+    _inner: S,
+}
+
+impl<S> WmHyperService<S> {
+    /// Wraps `inner`, using `client` for detection
+    pub fn new(client: AsyncWmClient, inner: S) -> WmHyperService<S> {
+        WmHyperService { _client: client, _inner: inner }
+    }
+}
+
+impl<S, B> hyper::service::Service<http::Request<B>> for WmHyperService<S> where
+    S: hyper::service::Service<http::Request<B>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    B: Send + 'static {
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = std::pin::Pin<Box<dyn std::future::Future<Output=Result<S::Response, S::Error>> + Send>>;
+
+    fn call(&self, mut req: http::Request<B>) -> Self::Future {
+        let client = self._client.clone();
+        let inner = self._inner.clone();
+        Box::pin(async move {
+            if let Ok(device) = client.lookup_request(&req).await {
+                req.extensions_mut().insert(DetectedDevice { device, is_fallback: false });
+            }
+            inner.call(req).await
+        })
+    }
+}
@@ -1,10 +1,43 @@
 use lru::LruCache;
 use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
 use std::str::from_utf8;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, RwLock};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::time::Duration;
-use reqwest::blocking::Response;
 use reqwest::Error;
 include!("./wmclient.rs");
 include!("./model.rs");
-include!("./cache.rs");
\ No newline at end of file
+include!("./cache.rs");
+include!("./transport.rs");
+include!("./fixture.rs");
+include!("./client_hints.rs");
+include!("./headers.rs");
+include!("./capabilities.rs");
+include!("./capability_info.rs");
+include!("./profiles.rs");
+include!("./statistics.rs");
+include!("./catalog.rs");
+include!("./names.rs");
+include!("./os_version.rs");
+include!("./make_models.rs");
+include!("./enumerations.rs");
+include!("./snapshot.rs");
+include!("./enumeration_diff.rs");
+include!("./query.rs");
+include!("./device_diff.rs");
+#[cfg(feature = "async")]
+include!("./async_client.rs");
+#[cfg(feature = "http")]
+include!("./http.rs");
+#[cfg(feature = "tower")]
+include!("./tower.rs");
+#[cfg(feature = "axum")]
+include!("./axum.rs");
+#[cfg(feature = "actix")]
+include!("./actix.rs");
+#[cfg(feature = "hyper")]
+include!("./hyper.rs");
+#[cfg(feature = "enrich")]
+include!("./enrich.rs");
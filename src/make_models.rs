@@ -0,0 +1,68 @@
+/// MakeModels - all the make and model records listed by the WM server, in the order they were received.
+/// Records keep their missing marketing names and duplicates. It shares the data downloaded by the client for
+/// `get_all_device_makes` and `get_all_devices_for_make`, so it is cheap to get and to clone.
+/// Basic usage:
+/// ```no_run
+/// use wmclient::WmClient;
+/// let client = WmClient::new("http", "localhost", "8080", "").unwrap();
+/// let make_models = client.get_make_models().unwrap();
+/// for (brand, models) in make_models.by_brand() {
+///     println!("{}: {} models", brand, models.len());
+/// }
+/// let without_marketing_name = make_models.iter().filter(|m| m.marketing_name.is_none()).count();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MakeModels {
+    _records: Arc<Vec<MakeModel>>,
+}
+
+impl MakeModels {
+    /// Iterates over all the records
+    pub fn iter(&self) -> std::slice::Iter<'_, MakeModel> {
+        self._records.iter()
+    }
+
+    /// Number of records
+    pub fn len(&self) -> usize {
+        self._records.len()
+    }
+
+    /// True if there are no records
+    pub fn is_empty(&self) -> bool {
+        self._records.is_empty()
+    }
+
+    /// Records grouped by brand name, brands in alphabetical order
+    pub fn by_brand(&self) -> std::collections::BTreeMap<&str, Vec<&MakeModel>> {
+        let mut groups: std::collections::BTreeMap<&str, Vec<&MakeModel>> = std::collections::BTreeMap::new();
+        for record in self._records.iter() {
+            groups.entry(record.brand_name.as_str()).or_default().push(record);
+        }
+        groups
+    }
+
+    /// Records grouped by model name, models in alphabetical order. The same model name may be used by several brands
+    pub fn by_model(&self) -> std::collections::BTreeMap<&str, Vec<&MakeModel>> {
+        let mut groups: std::collections::BTreeMap<&str, Vec<&MakeModel>> = std::collections::BTreeMap::new();
+        for record in self._records.iter() {
+            groups.entry(record.model_name.as_str()).or_default().push(record);
+        }
+        groups
+    }
+}
+
+impl<'a> IntoIterator for &'a MakeModels {
+    type Item = &'a MakeModel;
+    type IntoIter = std::slice::Iter<'a, MakeModel>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl WmClient {
+    /// Returns all the make and model records of the WM server, see `MakeModels`
+    pub fn get_make_models(&self) -> Result<MakeModels, WmError> {
+        Ok(MakeModels { _records: self._makes_snapshot()?.make_models.clone() })
+    }
+}
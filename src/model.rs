@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 /// Holds info about WURFL microservice running server
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JSONInfoData {
     pub wurfl_api_version: String,
     pub wm_version: String,
@@ -13,6 +13,13 @@ pub struct JSONInfoData {
     ltime: String,
 }
 
+impl JSONInfoData {
+    /// Time when the WM server loaded its WURFL data
+    pub fn ltime(&self) -> &str {
+        &self.ltime
+    }
+}
+
 /// Holds the detected device data received from WURFL Microservice server.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct JSONDeviceData {
@@ -22,11 +29,21 @@ pub struct JSONDeviceData {
     pub ltime: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct JSONMakeModel {
-    brand_name: String,
-    model_name: String,
-    marketing_name: Option<String>
+/// Device attached to HTTP requests by the web framework integrations (for example `WmDetectionLayer`).
+/// `is_fallback` is true when detection failed and the configured fallback device is used instead.
+#[derive(Debug, Clone)]
+pub struct DetectedDevice {
+    pub device: JSONDeviceData,
+    pub is_fallback: bool,
+}
+
+/// A device make and model, as listed by the WM server. See `WmClient::get_make_models`
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct MakeModel {
+    pub brand_name: String,
+    pub model_name: String,
+    /// None when WURFL has no marketing name for the device
+    pub marketing_name: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Eq, Ord, PartialEq, PartialOrd)]
@@ -61,10 +78,42 @@ impl Request {
     }
 }
 
-// Custom error for WURFL handle operations
+/// Kind of a WmError, used to tell apart the errors that callers may want to handle differently.
+/// New kinds may be added in future versions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum WmErrorKind {
+    /// Any error that does not have a more specific kind
+    Generic,
+    /// A request header value could not be used for detection: not valid UTF-8, too long, or too many headers
+    InvalidHeader,
+    /// The WM server could not be reached: connection refused, DNS failure or timeout
+    Unreachable,
+    /// A capability name is not known by the WM server, or a capability profile is not valid
+    InvalidCapability,
+    /// A capability value is not valid for the capability type, see `CapabilityInfo`
+    InvalidValue,
+}
+
+// Custom error for WURFL handle operations. Fields can be read but not set outside this crate:
+// use WmError::new or WmError::with_kind to create one
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub struct WmError {
     pub msg: String,
+    pub kind: WmErrorKind,
+}
+
+impl WmError {
+    /// Creates a WmError of kind `WmErrorKind::Generic`
+    pub fn new(msg: String) -> WmError {
+        WmError { msg, kind: WmErrorKind::Generic }
+    }
+
+    /// Creates a WmError of the given kind
+    pub fn with_kind(kind: WmErrorKind, msg: String) -> WmError {
+        WmError { msg, kind }
+    }
 }
 
 impl std::fmt::Display for WmError {
@@ -75,6 +124,15 @@ impl std::fmt::Display for WmError {
 
 impl From<reqwest::Error> for WmError {
     fn from(reqw_err: Error) -> Self {
-        WmError{msg: reqw_err.to_string()}
+        if reqw_err.is_connect() || reqw_err.is_timeout() {
+            return WmError::with_kind(WmErrorKind::Unreachable, reqw_err.to_string());
+        }
+        WmError::new(reqw_err.to_string())
+    }
+}
+
+impl From<serde_json::Error> for WmError {
+    fn from(serde_err: serde_json::Error) -> Self {
+        WmError::new(serde_err.to_string())
     }
 }
\ No newline at end of file
@@ -0,0 +1,120 @@
+// Other names used for WURFL OS names, as (alias, WURFL name) pairs in compact form (see _compact_name)
+const OS_NAME_ALIASES: [(&str, &str); 8] = [
+    ("iphoneos", "ios"),
+    ("osx", "macosx"),
+    ("macos", "macosx"),
+    ("win", "windows"),
+    ("windowsphone", "windowsphoneos"),
+    ("winphone", "windowsphoneos"),
+    ("chromiumos", "chromeos"),
+    ("blackberry", "blackberryos"),
+];
+
+// Other names used for WURFL brand names, as (alias, WURFL name) pairs in compact form (see _compact_name)
+const BRAND_NAME_ALIASES: [(&str, &str); 6] = [
+    ("appleinc", "apple"),
+    ("samsungelectronics", "samsung"),
+    ("lgelectronics", "lg"),
+    ("googleinc", "google"),
+    ("htccorporation", "htc"),
+    ("rim", "blackberry"),
+];
+
+/// Result of an enumeration lookup done with a name that is normalized before being searched, see for example
+/// `WmClient::find_devices_for_make`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NormalizedLookup<T> {
+    /// The name as written in WURFL data, e.g. "iOS" when "iphone os" was searched
+    pub canonical_name: String,
+    pub values: T,
+}
+
+impl WmClient {
+    /// Same as `get_all_devices_for_make`, but `brand_name` is matched ignoring case, spaces and punctuation, and
+    /// known aliases (e.g. "Samsung Electronics") are recognized. The result holds the brand name as written in WURFL
+    pub fn find_devices_for_make(&self, brand_name: &str) -> Result<NormalizedLookup<Vec<JSONModelMktName>>, WmError> {
+        match self._canonical_make_name(brand_name)? {
+            Some(canonical_name) => {
+                let values = self.get_all_devices_for_make(canonical_name.clone())?;
+                Ok(NormalizedLookup { canonical_name, values })
+            }
+            None => Err(WmError::new(format!("Error getting data from WM server: brand {} does not exist or has no devices", brand_name))),
+        }
+    }
+
+    /// Same as `get_all_versions_for_os`, but `os_name` is matched ignoring case, spaces and punctuation, and
+    /// known aliases (e.g. "iPhone OS" for "iOS") are recognized. The result holds the OS name as written in WURFL
+    pub fn find_versions_for_os(&self, os_name: &str) -> Result<NormalizedLookup<Vec<String>>, WmError> {
+        match self._canonical_os_name(os_name)? {
+            Some(canonical_name) => {
+                let values = self.get_all_versions_for_os(&canonical_name)?;
+                Ok(NormalizedLookup { canonical_name, values })
+            }
+            None => Err(WmError::new(format!("Error getting data from WM server: {} does not exist or has no versions", os_name))),
+        }
+    }
+
+    /// Returns the brand name, as written in WURFL, matching `brand_name` as done by `find_devices_for_make`
+    pub fn resolve_make_name(&self, brand_name: &str) -> Result<String, WmError> {
+        self.find_devices_for_make(brand_name).map(|found| found.canonical_name)
+    }
+
+    /// Returns the OS name, as written in WURFL, matching `os_name` as done by `find_versions_for_os`
+    pub fn resolve_os_name(&self, os_name: &str) -> Result<String, WmError> {
+        self.find_versions_for_os(os_name).map(|found| found.canonical_name)
+    }
+
+    // Brand name, as written in WURFL, matching the given one once normalized
+    fn _canonical_make_name(&self, brand_name: &str) -> Result<Option<String>, WmError> {
+        let snapshot = self._makes_snapshot()?;
+        Ok(snapshot.make_names.find(brand_name, &BRAND_NAME_ALIASES).map(str::to_string))
+    }
+
+    // OS name, as written in WURFL, matching the given one once normalized
+    fn _canonical_os_name(&self, os_name: &str) -> Result<Option<String>, WmError> {
+        let snapshot = self._os_snapshot()?;
+        Ok(snapshot.os_names.find(os_name, &OS_NAME_ALIASES).map(str::to_string))
+    }
+}
+
+// Lower case letters, digits and '+' of a name: "Windows Phone OS" and "windows-phone os" are both "windowsphoneos"
+fn _compact_name(name: &str) -> String {
+    name.chars().filter(|c| c.is_alphanumeric() || *c == '+').flat_map(|c| c.to_lowercase()).collect()
+}
+
+// Names as written in WURFL (brands or OSes), indexed by their compact form so that normalized lookups do not go
+// through all of them
+#[derive(Debug, Clone, Default)]
+struct NameIndex {
+    names: std::collections::HashSet<String>,
+    // compact form of the names; several names may have the same one: the first in alphabetical order is kept, to be
+    // consistent
+    compact_names: HashMap<String, String>,
+}
+
+impl NameIndex {
+    fn new<'a, I: IntoIterator<Item=&'a str>>(names: I) -> NameIndex {
+        let mut index = NameIndex::default();
+        for name in names {
+            let compact = _compact_name(name);
+            if !compact.is_empty() && index.compact_names.get(&compact).is_none_or(|found| name < found.as_str()) {
+                index.compact_names.insert(compact, name.to_string());
+            }
+            index.names.insert(name.to_string());
+        }
+        index
+    }
+
+    // Finds the name that matches `name` exactly, or once normalized, or through one of `aliases`
+    fn find(&self, name: &str, aliases: &[(&str, &str)]) -> Option<&str> {
+        if let Some(found) = self.names.get(name) {
+            return Some(found);
+        }
+        let compact = _compact_name(name);
+        let found = match self.compact_names.get(&compact) {
+            Some(found) => Some(found),
+            None => aliases.iter().find(|(alias, _)| *alias == compact).and_then(|(_, target)| self.compact_names.get(*target)),
+        };
+        found.map(|found| found.as_str())
+    }
+}
@@ -0,0 +1,138 @@
+/// OsVersion - a device OS version, as found in WURFL `device_os_version` values, ordered by its numbers
+/// ("9" < "10" < "10.2.1") instead of alphabetically.
+/// Any string is accepted: versions are split in numbers and words (e.g. "11.0 Beta", "10_3", "XP").
+/// A word after the same numbers marks a pre-release, so "11.0 Beta" < "11.0"; trailing zeros do not change the order,
+/// so "8" and "8.0" sort next to each other (alphabetically, to keep the order total).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OsVersion {
+    _raw: String,
+    _parts: Vec<OsVersionPart>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum OsVersionPart {
+    Number(u64),
+    Word(String),
+}
+
+impl OsVersion {
+    /// Parses a version string. Surrounding spaces are ignored
+    pub fn parse(version: &str) -> OsVersion {
+        let raw = version.trim().to_string();
+        let mut parts = vec![];
+        let mut current = String::new();
+        for c in raw.chars().chain(std::iter::once(' ')) {
+            let same_kind = current.chars().next().map(|p| p.is_ascii_digit() == c.is_ascii_digit()).unwrap_or(true);
+            if (!c.is_alphanumeric() || !same_kind) && !current.is_empty() {
+                parts.push(match current.parse::<u64>() {
+                    Ok(number) => OsVersionPart::Number(number),
+                    Err(_) => OsVersionPart::Word(current.to_lowercase()),
+                });
+                current.clear();
+            }
+            if c.is_alphanumeric() {
+                current.push(c);
+            }
+        }
+        OsVersion { _raw: raw, _parts: parts }
+    }
+
+    /// The version as written in WURFL
+    pub fn as_str(&self) -> &str {
+        &self._raw
+    }
+
+    /// The leading numbers of the version: [10, 2, 1] for "10.2.1", [11, 0] for "11.0 Beta"
+    pub fn numbers(&self) -> Vec<u64> {
+        self._parts.iter().map_while(|p| match p {
+            OsVersionPart::Number(n) => Some(*n),
+            OsVersionPart::Word(_) => None,
+        }).collect()
+    }
+
+    /// The first number of the version, if it starts with a number
+    pub fn major(&self) -> Option<u64> {
+        self.numbers().first().copied()
+    }
+
+    /// Compares versions by their numbers and words only: unlike `cmp`, "8" and "8.0" are equal
+    pub fn cmp_version(&self, other: &OsVersion) -> std::cmp::Ordering {
+        use std::cmp::Ordering;
+        let mut index = 0;
+        loop {
+            let ordering = match (self._parts.get(index), other._parts.get(index)) {
+                (None, None) => return Ordering::Equal,
+                (Some(part), None) => _trailing_part_ordering(part),
+                (None, Some(part)) => _trailing_part_ordering(part).reverse(),
+                (Some(OsVersionPart::Number(a)), Some(OsVersionPart::Number(b))) => a.cmp(b),
+                (Some(OsVersionPart::Word(a)), Some(OsVersionPart::Word(b))) => a.cmp(b),
+                // a pre-release word sorts before any number
+                (Some(OsVersionPart::Word(_)), Some(OsVersionPart::Number(_))) => Ordering::Less,
+                (Some(OsVersionPart::Number(_)), Some(OsVersionPart::Word(_))) => Ordering::Greater,
+            };
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+            index += 1;
+        }
+    }
+}
+
+// Ordering of a version having `part` where the other version has ended: 0 adds nothing, a number makes it newer,
+// a word makes it a pre-release
+fn _trailing_part_ordering(part: &OsVersionPart) -> std::cmp::Ordering {
+    match part {
+        OsVersionPart::Number(0) => std::cmp::Ordering::Equal,
+        OsVersionPart::Number(_) => std::cmp::Ordering::Greater,
+        OsVersionPart::Word(_) => std::cmp::Ordering::Less,
+    }
+}
+
+impl Ord for OsVersion {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.cmp_version(other).then_with(|| self._raw.cmp(&other._raw))
+    }
+}
+
+impl PartialOrd for OsVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl std::fmt::Display for OsVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self._raw)
+    }
+}
+
+impl std::str::FromStr for OsVersion {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(OsVersion::parse(s))
+    }
+}
+
+impl WmClient {
+    /// Returns the versions of the given OS, oldest first. `os_name` is matched as done by `get_all_versions_for_os`
+    pub fn get_os_versions(&self, os_name: &str) -> Result<Vec<OsVersion>, WmError> {
+        let mut versions: Vec<OsVersion> = self.get_all_versions_for_os(os_name)?.iter().map(|v| OsVersion::parse(v)).collect();
+        versions.sort();
+        Ok(versions)
+    }
+
+    /// Returns the versions of the given OS from `from` to `to`, both included, oldest first.
+    /// Bounds are compared with `OsVersion::cmp_version`, so `versions_between("Android", "8", "11")` includes "8.0" and "11.0"
+    pub fn versions_between(&self, os_name: &str, from: &str, to: &str) -> Result<Vec<OsVersion>, WmError> {
+        let (from, to) = (OsVersion::parse(from), OsVersion::parse(to));
+        Ok(self.get_os_versions(os_name)?.into_iter()
+            .filter(|v| v.cmp_version(&from).is_ge() && v.cmp_version(&to).is_le())
+            .collect())
+    }
+
+    /// Returns the newest version of the given OS, None if WURFL has no versions for it
+    pub fn latest_version(&self, os_name: &str) -> Result<Option<OsVersion>, WmError> {
+        Ok(self.get_os_versions(os_name)?.pop())
+    }
+}
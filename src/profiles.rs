@@ -0,0 +1,162 @@
+/// CapabilityProfile - named list of capabilities to be registered on a client with `WmClient::register_profile`.
+/// Lookups done through `WmClient::profile` return the profile capabilities, using a cache that is
+/// separated from the ones of the client and of the other profiles.
+/// Basic usage:
+/// ```no_run
+/// use wmclient::{CapabilityProfile, WmClient};
+/// let mut client = WmClient::new("http", "localhost", "8080", "").unwrap();
+/// client.set_cache_size(10000);
+/// client.register_profile(CapabilityProfile::new("ads", vec!["is_smartphone", "form_factor", "resolution_width"])).unwrap();
+/// let device = client.profile("ads").unwrap().lookup_useragent("Mozilla/5.0 (iPhone...)".to_string());
+/// ```
+#[derive(Debug, Clone)]
+pub struct CapabilityProfile {
+    name: String,
+    capabilities: Vec<String>,
+}
+
+impl CapabilityProfile {
+    /// Creates a profile with the given name and static and/or virtual capability names
+    pub fn new(name: &str, capabilities: Vec<&str>) -> CapabilityProfile {
+        CapabilityProfile { name: name.to_string(), capabilities: capabilities.iter().map(|c| c.to_string()).collect() }
+    }
+
+    /// Name of the profile
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Capabilities of the profile
+    pub fn capabilities(&self) -> &[String] {
+        &self.capabilities
+    }
+}
+
+/// Usage statistics of a capability profile
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ProfileStats {
+    /// Number of lookups done through the profile
+    pub lookups: u64,
+    /// Number of lookups served by the profile cache
+    pub cache_hits: u64,
+    /// Number of lookups that returned an error
+    pub errors: u64,
+}
+
+// A profile registered on a client, with its own cache and counters
+struct RegisteredProfile {
+    caps: CapabilitySet,
+    cache: Option<Cache>,
+    lookups: AtomicU64,
+    errors: AtomicU64,
+}
+
+/// ProfileClient - performs lookups on behalf of a capability profile, see `WmClient::profile`
+pub struct ProfileClient<'a> {
+    _client: &'a WmClient,
+    _name: &'a str,
+    _profile: &'a RegisteredProfile,
+}
+
+impl WmClient {
+    /// register_profile - registers a capability profile, to be used with `profile`.
+    /// All the profile capabilities must be among the server `static_caps` or `virtual_caps`, and no other profile with the
+    /// same name must have been registered: otherwise a `WmErrorKind::InvalidCapability` error is returned.
+    /// If the client has a cache (see `set_cache_size`), the profile gets its own cache of the same size.
+    pub fn register_profile(&mut self, profile: CapabilityProfile) -> Result<(), WmError> {
+        if self._profiles.contains_key(&profile.name) {
+            return Err(WmError::with_kind(WmErrorKind::InvalidCapability,
+                                          format!("Capability profile {} is already registered", profile.name)));
+        }
+        let cap_list = profile.capabilities.iter().map(|c| c.as_str()).collect();
+        let caps = match CapabilitySet::try_new(self, cap_list, CapabilityParsing::Strict) {
+            Ok(caps) => caps,
+            Err(err) => return Err(WmError::with_kind(err.kind, format!("Invalid profile {}: {}", profile.name, err.msg))),
+        };
+        let cache = self._cache.as_ref().map(|c| Cache::new(c.get_max_size()));
+        self._profiles.insert(profile.name, RegisteredProfile {
+            caps,
+            cache,
+            lookups: AtomicU64::new(0),
+            errors: AtomicU64::new(0),
+        });
+        Ok(())
+    }
+
+    /// profile - returns the client used to perform lookups with the registered profile `name`
+    pub fn profile(&self, name: &str) -> Result<ProfileClient<'_>, WmError> {
+        match self._profiles.get_key_value(name) {
+            Some((name, profile)) => Ok(ProfileClient { _client: self, _name: name.as_str(), _profile: profile }),
+            None => Err(WmError::with_kind(WmErrorKind::InvalidCapability, format!("Capability profile {} is not registered", name))),
+        }
+    }
+
+    /// Returns the names of the registered profiles, sorted
+    pub fn get_profile_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self._profiles.keys().cloned().collect();
+        names.sort();
+        names
+    }
+}
+
+impl<'a> ProfileClient<'a> {
+    /// Name of the profile
+    pub fn name(&self) -> &str {
+        self._name
+    }
+
+    /// Capabilities requested by the profile lookups
+    pub fn capabilities(&self) -> &CapabilitySet {
+        &self._profile.caps
+    }
+
+    /// Same as `WmClient::lookup_useragent`, returning the profile capabilities
+    pub fn lookup_useragent(&self, user_agent: String) -> Result<JSONDeviceData, WmError> {
+        self._count(self._client._lookup_useragent(user_agent, Some(&self._profile.caps), self._profile.cache.as_ref()))
+    }
+
+    /// Same as `WmClient::lookup_device_id`, returning the profile capabilities
+    pub fn lookup_device_id(&self, device_id: String) -> Result<JSONDeviceData, WmError> {
+        self._count(self._client._lookup_device_id(device_id, Some(&self._profile.caps), self._profile.cache.as_ref()))
+    }
+
+    /// Same as `WmClient::lookup_headers`, returning the profile capabilities
+    pub fn lookup_headers<U, V, T: IntoIterator<Item=(U, V)>>(&self, in_headers: T) -> Result<JSONDeviceData, WmError> where
+        U: ToString,
+        V: AsRef<[u8]> {
+        self._count(self._client._lookup_headers(in_headers, Some(&self._profile.caps), self._profile.cache.as_ref()))
+    }
+
+    /// Same as `WmClient::lookup_header_map`, returning the profile capabilities
+    #[cfg(feature = "http")]
+    pub fn lookup_header_map(&self, header_map: &http::HeaderMap) -> Result<JSONDeviceData, WmError> {
+        self._count(self._client._lookup_header_map(header_map, Some(&self._profile.caps), self._profile.cache.as_ref()))
+    }
+
+    /// Same as `WmClient::lookup_request`, returning the profile capabilities
+    #[cfg(feature = "http")]
+    pub fn lookup_request<B>(&self, req: &http::Request<B>) -> Result<JSONDeviceData, WmError> {
+        self.lookup_header_map(req.headers())
+    }
+
+    /// Returns the usage statistics of the profile
+    pub fn stats(&self) -> ProfileStats {
+        let cache_hits = match &self._profile.cache {
+            Some(cache) => cache.get_hits_and_misses().0,
+            None => 0,
+        };
+        ProfileStats {
+            lookups: self._profile.lookups.load(Ordering::Relaxed),
+            cache_hits,
+            errors: self._profile.errors.load(Ordering::Relaxed),
+        }
+    }
+
+    fn _count(&self, result: Result<JSONDeviceData, WmError>) -> Result<JSONDeviceData, WmError> {
+        self._profile.lookups.fetch_add(1, Ordering::Relaxed);
+        if result.is_err() {
+            self._profile.errors.fetch_add(1, Ordering::Relaxed);
+        }
+        result
+    }
+}
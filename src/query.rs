@@ -0,0 +1,312 @@
+// Lookups run at the same time by DeviceQuery, unless set with DeviceQuery::concurrency
+const DEFAULT_QUERY_CONCURRENCY: usize = 4;
+
+/// Comparison done by a `CapabilityPredicate`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PredicateOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// CapabilityPredicate - a condition on a capability value, used by `DeviceQuery`.
+/// Values of `int` capabilities (see `CapabilityRegistry`) are compared as numbers; the others are compared as
+/// strings by `Eq` and `Ne`, and as version numbers (see `OsVersion`) by the other operators, so that
+/// `CapabilityPredicate::at_least("device_os_version", "10")` matches "10.0" and "11".
+/// Devices that do not have the capability never match.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CapabilityPredicate {
+    pub name: String,
+    pub op: PredicateOp,
+    pub value: String,
+}
+
+impl CapabilityPredicate {
+    /// Creates a predicate comparing the capability `name` to `value`
+    pub fn new(name: &str, op: PredicateOp, value: &str) -> CapabilityPredicate {
+        CapabilityPredicate { name: name.to_string(), op, value: value.to_string() }
+    }
+
+    /// The capability is `value`
+    pub fn equals(name: &str, value: &str) -> CapabilityPredicate {
+        CapabilityPredicate::new(name, PredicateOp::Eq, value)
+    }
+
+    /// The capability is not `value`
+    pub fn not_equals(name: &str, value: &str) -> CapabilityPredicate {
+        CapabilityPredicate::new(name, PredicateOp::Ne, value)
+    }
+
+    /// The capability is less than `value`
+    pub fn less_than(name: &str, value: &str) -> CapabilityPredicate {
+        CapabilityPredicate::new(name, PredicateOp::Lt, value)
+    }
+
+    /// The capability is less than or equal to `value`
+    pub fn at_most(name: &str, value: &str) -> CapabilityPredicate {
+        CapabilityPredicate::new(name, PredicateOp::Le, value)
+    }
+
+    /// The capability is greater than `value`
+    pub fn greater_than(name: &str, value: &str) -> CapabilityPredicate {
+        CapabilityPredicate::new(name, PredicateOp::Gt, value)
+    }
+
+    /// The capability is greater than or equal to `value`
+    pub fn at_least(name: &str, value: &str) -> CapabilityPredicate {
+        CapabilityPredicate::new(name, PredicateOp::Ge, value)
+    }
+
+    /// True if the device capability satisfies the predicate
+    pub fn matches(&self, device: &JSONDeviceData) -> bool {
+        let value = match device.capabilities.get(&self.name) {
+            Some(value) => value,
+            None => return false,
+        };
+        let ordering = if self._value_type() == Some(CapabilityType::Int) {
+            match (value.parse::<i64>(), self.value.parse::<i64>()) {
+                (Ok(device_value), Ok(predicate_value)) => device_value.cmp(&predicate_value),
+                _ => return false,
+            }
+        } else if matches!(self.op, PredicateOp::Eq | PredicateOp::Ne) {
+            value.cmp(&self.value)
+        } else {
+            OsVersion::parse(value).cmp_version(&OsVersion::parse(&self.value))
+        };
+        match self.op {
+            PredicateOp::Eq => ordering.is_eq(),
+            PredicateOp::Ne => ordering.is_ne(),
+            PredicateOp::Lt => ordering.is_lt(),
+            PredicateOp::Le => ordering.is_le(),
+            PredicateOp::Gt => ordering.is_gt(),
+            PredicateOp::Ge => ordering.is_ge(),
+        }
+    }
+
+    fn _value_type(&self) -> Option<CapabilityType> {
+        CapabilityRegistry::embedded().get(&self.name).map(|cap_info| cap_info.value_type)
+    }
+
+    // Checks the value against the capability type: bool and enum values can only be compared for equality
+    fn _validate(&self) -> Result<(), WmError> {
+        let cap_info = match CapabilityRegistry::embedded().get(&self.name) {
+            Some(cap_info) => cap_info,
+            None => return Ok(()),
+        };
+        let ordered = !matches!(self.op, PredicateOp::Eq | PredicateOp::Ne);
+        if ordered && matches!(cap_info.value_type, CapabilityType::Bool | CapabilityType::Enum) {
+            return Err(WmError::with_kind(WmErrorKind::InvalidValue,
+                                          format!("Capability {} can only be compared with Eq or Ne", self.name)));
+        }
+        cap_info.parse(&self.value).map(|_| ())
+    }
+}
+
+/// Counters of the devices processed by `DeviceQuery::run`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct QueryStats {
+    /// Number of devices looked up
+    pub candidates: u64,
+    /// Number of devices matching the query
+    pub matched: u64,
+    /// Number of lookups that returned an error, such as an unknown device ID
+    pub errors: u64,
+}
+
+/// DeviceQuery - finds the devices that satisfy a brand, an OS and capability predicates.
+/// The enumeration data of the WM server lists brands and models but not their WURFL IDs, so the candidate
+/// device IDs are given to `run` (for example the `wurfl_id` column of logs enriched by `LogEnricher`).
+/// Brand and OS names are first resolved as done by `find_devices_for_make` and `find_versions_for_os`: if the
+/// WM server does not list them, no device can match and no lookup is done. Each candidate is then looked up
+/// with only the capabilities the query needs (see `CapabilitySet`), going through the client cache, by up to
+/// `concurrency` threads; matching devices are passed to the caller as soon as they are found, in no particular order.
+/// An unreachable WM server stops the query with an error, any other lookup error skips the device.
+/// Basic usage:
+/// ```no_run
+/// use wmclient::{CapabilityPredicate, WmClient};
+/// let client = WmClient::new("http", "localhost", "8080", "").unwrap();
+/// let device_ids = vec!["samsung_sm_t970_ver1".to_string(), "samsung_sm_g960f_ver1".to_string()];
+/// let stats = client.query()
+///     .brand("Samsung")
+///     .filter(CapabilityPredicate::equals("is_tablet", "true"))
+///     .filter(CapabilityPredicate::at_least("resolution_width", "1200"))
+///     .capabilities(vec!["model_name", "marketing_name"])
+///     .run(device_ids, |device| println!("{}", device.capabilities["model_name"]))
+///     .unwrap();
+/// println!("{} of {} devices match", stats.matched, stats.candidates);
+/// ```
+pub struct DeviceQuery<'a> {
+    _client: &'a WmClient,
+    _brand: Option<String>,
+    _os: Option<String>,
+    _predicates: Vec<CapabilityPredicate>,
+    _caps: Vec<String>,
+    _concurrency: usize,
+    _limit: Option<u64>,
+}
+
+impl WmClient {
+    /// Creates a query matching all devices, see `DeviceQuery`
+    pub fn query(&self) -> DeviceQuery<'_> {
+        DeviceQuery {
+            _client: self,
+            _brand: None,
+            _os: None,
+            _predicates: vec![],
+            _caps: vec![],
+            _concurrency: DEFAULT_QUERY_CONCURRENCY,
+            _limit: None,
+        }
+    }
+}
+
+impl<'a> DeviceQuery<'a> {
+    /// Only devices of the given brand match
+    pub fn brand(mut self, brand_name: &str) -> DeviceQuery<'a> {
+        self._brand = Some(brand_name.to_string());
+        self
+    }
+
+    /// Only devices running the given OS match
+    pub fn os(mut self, os_name: &str) -> DeviceQuery<'a> {
+        self._os = Some(os_name.to_string());
+        self
+    }
+
+    /// Only devices satisfying the predicate match. All the predicates must be satisfied
+    pub fn filter(mut self, predicate: CapabilityPredicate) -> DeviceQuery<'a> {
+        self._predicates.push(predicate);
+        self
+    }
+
+    /// Matching devices hold these capabilities too, besides `wurfl_id` and the ones the query is about.
+    /// Unknown names make `run` fail
+    pub fn capabilities(mut self, cap_list: Vec<&str>) -> DeviceQuery<'a> {
+        self._caps = cap_list.iter().map(|name| name.to_string()).collect();
+        self
+    }
+
+    /// Maximum number of lookups run at the same time, 4 by default
+    pub fn concurrency(mut self, concurrency: usize) -> DeviceQuery<'a> {
+        self._concurrency = std::cmp::max(1, concurrency);
+        self
+    }
+
+    /// Stops the query once this number of devices is found; with 0 no lookup is done
+    pub fn limit(mut self, limit: u64) -> DeviceQuery<'a> {
+        self._limit = Some(limit);
+        self
+    }
+
+    /// Looks up the given device IDs and calls `on_match` with each device matching the query.
+    /// Returns a `WmErrorKind::InvalidCapability` error if a predicate or `capabilities` name a capability unknown to
+    /// the WM server (or if `brand` or `os` are used and the server lacks `brand_name` or `device_os`), and a `WmErrorKind::InvalidValue` error if a predicate value is not valid for the capability type
+    pub fn run<I, F>(&self, device_ids: I, mut on_match: F) -> Result<QueryStats, WmError> where
+        I: IntoIterator<Item=String>,
+        I::IntoIter: Send,
+        F: FnMut(JSONDeviceData) {
+        let mut names: Vec<&str> = self._predicates.iter().map(|p| p.name.as_str()).collect();
+        names.extend(self._caps.iter().map(|name| name.as_str()));
+        self._client._validate_capabilities(names, CapabilityParsing::Strict, true, true)?;
+        for predicate in &self._predicates {
+            predicate._validate()?;
+        }
+
+        let mut stats = QueryStats::default();
+        if self._limit == Some(0) {
+            return Ok(stats);
+        }
+        let mut cap_list: Vec<&str> = self._predicates.iter().map(|p| p.name.as_str()).collect();
+        cap_list.extend(self._caps.iter().map(|name| name.as_str()));
+        let brand = match &self._brand {
+            Some(brand_name) => match self._client._canonical_make_name(brand_name)? {
+                Some(canonical_name) => Some(canonical_name),
+                None => return Ok(stats),
+            },
+            None => None,
+        };
+        let os = match &self._os {
+            Some(os_name) => match self._client._canonical_os_name(os_name)? {
+                Some(canonical_name) => Some(canonical_name),
+                None => return Ok(stats),
+            },
+            None => None,
+        };
+        if brand.is_some() {
+            cap_list.push("brand_name");
+        }
+        if os.is_some() {
+            cap_list.push("device_os");
+        }
+        let caps = CapabilitySet::try_new(self._client, cap_list, CapabilityParsing::Strict)?;
+        let is_match = |device: &JSONDeviceData| {
+            let has = |name: &str, expected: &Option<String>| match expected {
+                Some(expected) => device.capabilities.get(name) == Some(expected),
+                None => true,
+            };
+            has("brand_name", &brand) && has("device_os", &os) && self._predicates.iter().all(|p| p.matches(device))
+        };
+
+        let device_ids = Mutex::new(device_ids.into_iter());
+        let stop = AtomicBool::new(false);
+        std::thread::scope(|scope| {
+            // bounded, so that lookups do not run far ahead of the caller
+            let (sender, receiver) = std::sync::mpsc::sync_channel(self._concurrency);
+            for _ in 0..self._concurrency {
+                let sender = sender.clone();
+                let (device_ids, stop, caps, is_match) = (&device_ids, &stop, &caps, &is_match);
+                scope.spawn(move || {
+                    while !stop.load(Ordering::Relaxed) {
+                        let device_id = match device_ids.lock() {
+                            Ok(mut ids) => ids.next(),
+                            Err(_) => None,
+                        };
+                        let device_id = match device_id {
+                            Some(device_id) => device_id,
+                            None => break,
+                        };
+                        let result = self._client.lookup_device_id_with(caps, device_id).map(|device| Some(device).filter(is_match));
+                        if sender.send(result).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+            drop(sender);
+
+            for result in receiver {
+                stats.candidates += 1;
+                match result {
+                    Ok(Some(device)) => {
+                        stats.matched += 1;
+                        on_match(device);
+                        if self._limit.is_some_and(|limit| stats.matched >= limit) {
+                            break;
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(wm_err) if wm_err.kind == WmErrorKind::Unreachable => {
+                        stop.store(true, Ordering::Relaxed);
+                        return Err(wm_err);
+                    }
+                    Err(_) => stats.errors += 1,
+                }
+            }
+            // dropping the receiver stops the threads still running
+            stop.store(true, Ordering::Relaxed);
+            Ok(stats)
+        })
+    }
+
+    /// Same as `run`, returning the matching devices
+    pub fn collect<I>(&self, device_ids: I) -> Result<Vec<JSONDeviceData>, WmError> where
+        I: IntoIterator<Item=String>,
+        I::IntoIter: Send {
+        let mut devices = vec![];
+        self.run(device_ids, |device| devices.push(device))?;
+        Ok(devices)
+    }
+}
@@ -0,0 +1,127 @@
+// Version of the format written by EnumerationSnapshot::write
+const ENUMERATION_SNAPSHOT_VERSION: u32 = 1;
+
+/// EnumerationSnapshot - the enumeration data of a WM server (makes, models, OSes and versions) together with the
+/// server info, such as `wurfl_info` and the WURFL data load time. It can be written to a file with
+/// `WmClient::export_enumerations` and loaded with `WmClient::import_enumerations`, for example to fill makes and
+/// OS dropdowns while the WM server is not available.
+/// Basic usage:
+/// ```no_run
+/// use std::fs::File;
+/// use wmclient::{ReqwestTransport, WmClient};
+/// let client = WmClient::new("http", "localhost", "8080", "").unwrap();
+/// client.export_enumerations(File::create("enumerations.json").unwrap()).unwrap();
+///
+/// // later, even if the WM server is down
+/// let transport = ReqwestTransport::new().unwrap();
+/// let snapshot = wmclient::EnumerationSnapshot::read(File::open("enumerations.json").unwrap()).unwrap();
+/// let client = WmClient::with_enumerations("http", "localhost", "8080", "", Box::new(transport), snapshot).unwrap();
+/// let makes = client.get_all_device_makes().unwrap();
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnumerationSnapshot {
+    /// Version of the snapshot format
+    pub version: u32,
+    /// Info of the WM server the data comes from
+    pub info: JSONInfoData,
+    /// Make and model records, as listed by the WM server
+    pub make_models: Vec<MakeModel>,
+    /// Versions of each OS, as listed by the WM server
+    pub os_versions: std::collections::BTreeMap<String, Vec<String>>,
+}
+
+impl EnumerationSnapshot {
+    /// Reads a snapshot written by `write`
+    pub fn read<R: std::io::Read>(reader: R) -> Result<EnumerationSnapshot, WmError> {
+        let snapshot: EnumerationSnapshot = match serde_json::from_reader(reader) {
+            Ok(snapshot) => snapshot,
+            Err(err) => return Err(WmError::new(format!("Could not read enumeration snapshot: {}", err))),
+        };
+        if snapshot.version > ENUMERATION_SNAPSHOT_VERSION {
+            return Err(WmError::new(format!("Unsupported enumeration snapshot version {}, this client reads up to version {}",
+                                            snapshot.version, ENUMERATION_SNAPSHOT_VERSION)));
+        }
+        Ok(snapshot)
+    }
+
+    /// Writes the snapshot as JSON
+    pub fn write<W: Write>(&self, writer: W) -> Result<(), WmError> {
+        match serde_json::to_writer(writer, self) {
+            Ok(()) => Ok(()),
+            Err(err) => Err(WmError::new(format!("Could not write enumeration snapshot: {}", err))),
+        }
+    }
+}
+
+impl WmClient {
+    /// Creates a client whose enumeration data comes from `snapshot`, so that it is available without downloading it.
+    /// The snapshot is used only if it has the WURFL data version loaded by the WM server: an older one is ignored and
+    /// the enumerations are downloaded when needed.
+    /// If the WM server is unreachable, the client is created anyway using the server info stored in the snapshot:
+    /// enumerations work offline, lookups fail until the server is back. Any other error is returned.
+    pub fn with_enumerations(scheme: &str, host: &str, port: &str, base_uri: &str, transport: Box<dyn Transport>, snapshot: EnumerationSnapshot) -> Result<WmClient, WmError> {
+        let mut wm_client = WmClient::_create(scheme, host, port, base_uri, transport);
+        match wm_client.get_info() {
+            Ok(info) => {
+                let current = info.ltime == snapshot.info.ltime;
+                wm_client._set_info(info);
+                if current {
+                    wm_client._import_snapshot(snapshot);
+                }
+            }
+            Err(err) if err.kind == WmErrorKind::Unreachable => {
+                wm_client._set_info(snapshot.info.clone());
+                wm_client._import_snapshot(snapshot);
+            }
+            Err(err) => return Err(err),
+        }
+        Ok(wm_client)
+    }
+
+    /// Returns the current enumeration data, downloading it if needed, with the current WM server info
+    pub fn get_enumeration_snapshot(&self) -> Result<EnumerationSnapshot, WmError> {
+        let info = self.get_info()?;
+        // enumerations loaded before a WURFL data update are stale: get the ones matching the info
+        self._clear_caches_if_needed(info.ltime.clone());
+        let makes = self._makes_snapshot()?;
+        let oses = self._os_snapshot()?;
+        let mut os_versions = std::collections::BTreeMap::new();
+        for (os, versions) in &oses.os_versions_map {
+            let mut versions = versions.clone();
+            versions.sort_by_cached_key(|v| OsVersion::parse(v));
+            os_versions.insert(os.clone(), versions);
+        }
+        Ok(EnumerationSnapshot {
+            version: ENUMERATION_SNAPSHOT_VERSION,
+            info,
+            make_models: makes.make_models.to_vec(),
+            os_versions,
+        })
+    }
+
+    /// Writes the current enumeration data and WM server info to `writer`, see `EnumerationSnapshot`
+    pub fn export_enumerations<W: Write>(&self, writer: W) -> Result<(), WmError> {
+        self.get_enumeration_snapshot()?.write(writer)
+    }
+
+    /// Replaces the enumeration data with the snapshot read from `reader`, written by `export_enumerations`.
+    /// The data is used until it expires (see `set_enumerations_ttl`), `refresh_enumerations` is called or a lookup
+    /// shows that the WM server has loaded a different WURFL data version
+    pub fn import_enumerations<R: std::io::Read>(&self, reader: R) -> Result<(), WmError> {
+        self._import_snapshot(EnumerationSnapshot::read(reader)?);
+        Ok(())
+    }
+
+    fn _import_snapshot(&self, snapshot: EnumerationSnapshot) {
+        let mut os_version_pairs = vec![];
+        for (device_os, versions) in snapshot.os_versions {
+            os_version_pairs.extend(versions.into_iter().map(|device_os_version| JSONDeviceOsVersions { device_os: device_os.clone(), device_os_version }));
+        }
+        self._swap_makes(Some(Arc::new(_build_makes_snapshot(snapshot.make_models))));
+        self._swap_oses(Some(Arc::new(_build_os_snapshot(os_version_pairs))));
+        // the data is tied to the WURFL version it comes from
+        if let Ok(mut ltime) = self._ltime.lock() {
+            *ltime = snapshot.info.ltime;
+        }
+    }
+}
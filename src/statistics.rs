@@ -0,0 +1,164 @@
+/// Capabilities used by `DeviceStatistics`: lookups whose results are added to the statistics must request them
+pub const STATISTICS_CAPABILITIES: [&str; 5] = ["brand_name", "device_os", "device_os_version", "form_factor", "is_robot"];
+
+// Value counted when a device does not have a capability, or has it empty
+const UNKNOWN_VALUE: &str = "unknown";
+
+/// DeviceStatistics - aggregates detected devices into traffic shares by form factor, brand, OS, OS version and
+/// bot vs human traffic.
+/// Devices can come from any lookup, as long as it returns the `STATISTICS_CAPABILITIES`.
+/// Basic usage:
+/// ```no_run
+/// use wmclient::{CapabilityParsing, CapabilitySet, DeviceStatistics, WmClient, STATISTICS_CAPABILITIES};
+/// let client = WmClient::new("http", "localhost", "8080", "").unwrap();
+/// let caps = CapabilitySet::try_new(&client, STATISTICS_CAPABILITIES.to_vec(), CapabilityParsing::Strict).unwrap();
+/// let mut stats = DeviceStatistics::new();
+/// for ua in ["Mozilla/5.0 (iPhone...)", "Mozilla/5.0 (Linux; Android 10...)"] {
+///     stats.add(&client.lookup_useragent_with(&caps, ua.to_string()).unwrap());
+/// }
+/// let report = stats.report();
+/// println!("{}", report.to_json().unwrap());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DeviceStatistics {
+    _total: u64,
+    _form_factors: HashMap<String, u64>,
+    _brands: HashMap<String, u64>,
+    _oses: HashMap<String, u64>,
+    _os_versions: HashMap<String, u64>,
+    _traffic: HashMap<String, u64>,
+}
+
+/// Number of devices with the same value of a statistics dimension, and their share of the total
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatisticsEntry {
+    pub value: String,
+    pub count: u64,
+    /// Percentage of the devices added to the statistics, from 0 to 100
+    pub percentage: f64,
+}
+
+/// Traffic shares computed by `DeviceStatistics::report`. Entries are sorted by descending count.
+/// Devices without a capability are counted as `unknown`; OS versions are prefixed by the OS name (e.g. `Android 10`),
+/// traffic is either `bot`, `human` or `unknown`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatisticsReport {
+    pub total: u64,
+    pub form_factor: Vec<StatisticsEntry>,
+    pub brand_name: Vec<StatisticsEntry>,
+    pub device_os: Vec<StatisticsEntry>,
+    pub device_os_version: Vec<StatisticsEntry>,
+    pub traffic: Vec<StatisticsEntry>,
+}
+
+impl DeviceStatistics {
+    /// Creates empty statistics
+    pub fn new() -> DeviceStatistics {
+        DeviceStatistics::default()
+    }
+
+    /// Adds a detected device to the statistics
+    pub fn add(&mut self, device: &JSONDeviceData) {
+        let value = |name: &str| match device.capabilities.get(name) {
+            Some(v) if !v.is_empty() => v.clone(),
+            _ => UNKNOWN_VALUE.to_string(),
+        };
+        self._total += 1;
+        *self._form_factors.entry(value("form_factor")).or_insert(0) += 1;
+        *self._brands.entry(value("brand_name")).or_insert(0) += 1;
+        let os = value("device_os");
+        let os_version = match device.capabilities.get("device_os_version") {
+            Some(v) if !v.is_empty() && os != UNKNOWN_VALUE => format!("{} {}", os, v),
+            _ => format!("{} {}", os, UNKNOWN_VALUE),
+        };
+        *self._oses.entry(os).or_insert(0) += 1;
+        *self._os_versions.entry(os_version).or_insert(0) += 1;
+        let traffic = match device.capabilities.get("is_robot").map(|v| v.as_str()) {
+            Some("true") => "bot",
+            Some("false") => "human",
+            _ => UNKNOWN_VALUE,
+        };
+        *self._traffic.entry(traffic.to_string()).or_insert(0) += 1;
+    }
+
+    /// Adds the devices counted by `other`, for example statistics computed by another thread
+    pub fn merge(&mut self, other: &DeviceStatistics) {
+        self._total += other._total;
+        for (counts, other_counts) in [
+            (&mut self._form_factors, &other._form_factors),
+            (&mut self._brands, &other._brands),
+            (&mut self._oses, &other._oses),
+            (&mut self._os_versions, &other._os_versions),
+            (&mut self._traffic, &other._traffic),
+        ] {
+            for (value, count) in other_counts {
+                *counts.entry(value.clone()).or_insert(0) += count;
+            }
+        }
+    }
+
+    /// Number of devices added to the statistics
+    pub fn total(&self) -> u64 {
+        self._total
+    }
+
+    /// Computes the counts and percentages of every dimension
+    pub fn report(&self) -> StatisticsReport {
+        StatisticsReport {
+            total: self._total,
+            form_factor: self._entries(&self._form_factors),
+            brand_name: self._entries(&self._brands),
+            device_os: self._entries(&self._oses),
+            device_os_version: self._entries(&self._os_versions),
+            traffic: self._entries(&self._traffic),
+        }
+    }
+
+    fn _entries(&self, counts: &HashMap<String, u64>) -> Vec<StatisticsEntry> {
+        let mut entries: Vec<StatisticsEntry> = counts.iter().map(|(value, count)| StatisticsEntry {
+            value: value.clone(),
+            count: *count,
+            percentage: *count as f64 * 100.0 / self._total as f64,
+        }).collect();
+        entries.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+        entries
+    }
+}
+
+impl StatisticsReport {
+    /// Entries of every dimension, as (dimension name, entries) pairs
+    pub fn dimensions(&self) -> Vec<(&str, &[StatisticsEntry])> {
+        vec![
+            ("form_factor", &self.form_factor),
+            ("brand_name", &self.brand_name),
+            ("device_os", &self.device_os),
+            ("device_os_version", &self.device_os_version),
+            ("traffic", &self.traffic),
+        ]
+    }
+
+    /// Returns the report as a JSON document
+    pub fn to_json(&self) -> Result<String, WmError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Returns the report as CSV, with a `dimension,value,count,percentage` header. Percentages have two decimals
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("dimension,value,count,percentage\n");
+        for (dimension, entries) in self.dimensions() {
+            for entry in entries {
+                csv.push_str(&format!("{},{},{},{:.2}\n", dimension, _csv_field(&entry.value), entry.count, entry.percentage));
+            }
+        }
+        csv
+    }
+}
+
+// Quotes a CSV field when it contains separators, quotes or line breaks
+fn _csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
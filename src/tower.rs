@@ -0,0 +1,113 @@
+/// WmDetectionLayer - tower `Layer` that detects the device of every incoming HTTP request and attaches it to
+/// the request extensions as a `DetectedDevice`, before calling the wrapped service.
+/// Detection is done with `AsyncWmClient::lookup_header_map` on the request headers. When it fails or takes longer
+/// than the configured timeout, the fallback device (if any) is attached instead; without a fallback device the
+/// request reaches the inner service with no `DetectedDevice` extension.
+/// Basic usage:
+/// ```ignore
+/// let layer = WmDetectionLayer::new(async_client)
+///     .capabilities(vec!["brand_name", "model_name", "form_factor"])?
+///     .timeout(Duration::from_millis(200));
+/// let service = tower::ServiceBuilder::new().layer(layer).service(my_service);
+/// ```
+#[derive(Clone)]
+pub struct WmDetectionLayer {
+    _config: WmDetectionConfig,
+}
+
+#[derive(Clone)]
+struct WmDetectionConfig {
+    client: AsyncWmClient,
+    capabilities: Option<CapabilitySet>,
+    timeout: Option<Duration>,
+    fallback: Option<JSONDeviceData>,
+}
+
+impl WmDetectionLayer {
+    /// Creates a layer that attaches all the capabilities returned by `client`, without timeout nor fallback device
+    pub fn new(client: AsyncWmClient) -> WmDetectionLayer {
+        WmDetectionLayer {
+            _config: WmDetectionConfig { client, capabilities: None, timeout: None, fallback: None },
+        }
+    }
+
+    /// Detection requests the given capabilities (see `CapabilitySet`) instead of the ones requested by the client.
+    /// Returns a `WmErrorKind::InvalidCapability` error if a name is unknown to the WM server
+    pub fn capabilities(mut self, cap_list: Vec<&str>) -> Result<WmDetectionLayer, WmError> {
+        let caps = CapabilitySet::try_new(self._config.client.client(), cap_list, CapabilityParsing::Strict)?;
+        self._config.capabilities = Some(caps);
+        Ok(self)
+    }
+
+    /// Maximum time a detection can take: after that the request goes on with the fallback device, if any.
+    /// The timeout does not stop the lookup, that keeps running on the blocking thread pool until the HTTP call to the
+    /// WM server ends: use `WmClient::set_http_timeout` to bound it too
+    pub fn timeout(mut self, timeout: Duration) -> WmDetectionLayer {
+        self._config.timeout = Some(timeout);
+        self
+    }
+
+    /// Device attached to requests when detection fails or times out
+    pub fn fallback(mut self, device: JSONDeviceData) -> WmDetectionLayer {
+        self._config.fallback = Some(device);
+        self
+    }
+}
+
+impl WmDetectionConfig {
+    async fn detect(&self, headers: &http::HeaderMap) -> Option<DetectedDevice> {
+        let lookup = self.client._lookup_header_map(headers, self.capabilities.as_ref());
+        let result = match self.timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, lookup).await {
+                Ok(res) => res,
+                Err(_) => Err(WmError::new(format!("Device detection timed out after {} ms", timeout.as_millis()))),
+            },
+            None => lookup.await,
+        };
+        match result {
+            Ok(device) => Some(DetectedDevice { device, is_fallback: false }),
+            Err(_) => self.fallback.clone().map(|device| DetectedDevice { device, is_fallback: true }),
+        }
+    }
+}
+
+impl<S> tower_layer::Layer<S> for WmDetectionLayer {
+    type Service = WmDetectionService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        WmDetectionService { _inner: inner, _config: Arc::new(self._config.clone()) }
+    }
+}
+
+/// Service created by `WmDetectionLayer`
+#[derive(Clone)]
+pub struct WmDetectionService<S> {
+    _inner: S,
+    _config: Arc<WmDetectionConfig>,
+}
+
+impl<S, B> tower_service::Service<http::Request<B>> for WmDetectionService<S> where
+    S: tower_service::Service<http::Request<B>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    B: Send + 'static {
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = std::pin::Pin<Box<dyn std::future::Future<Output=Result<S::Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut std::task::Context<'_>) -> std::task::Poll<Result<(), Self::Error>> {
+        self._inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: http::Request<B>) -> Self::Future {
+        // the service that has been polled ready is the one that must be called
+        let clone = self._inner.clone();
+        let mut inner = std::mem::replace(&mut self._inner, clone);
+        let config = self._config.clone();
+        Box::pin(async move {
+            if let Some(detected) = config.detect(req.headers()).await {
+                req.extensions_mut().insert(detected);
+            }
+            inner.call(req).await
+        })
+    }
+}
@@ -0,0 +1,75 @@
+/// Transport - issues the HTTP calls to the WURFL Microservice server endpoints on behalf of a `WmClient`.
+/// The client builds the full endpoint URL and the JSON request body, the transport only has to send them and
+/// return the response body. Implementations are expected to send the `Content-Type: application/json` header
+/// and may use `wm_client_user_agent()` as the `User-Agent` header.
+/// `ReqwestTransport` is the default implementation; custom ones can be passed to `WmClient::with_transport`
+/// to use a different HTTP stack, add middleware or serve responses from memory in tests.
+pub trait Transport: Send + Sync {
+    /// Performs a GET request to the given `url` and returns the response body
+    fn get(&self, url: &str) -> Result<String, WmError>;
+
+    /// Performs a POST request sending the JSON `body` to the given `url` and returns the response body
+    fn post(&self, url: &str, body: &str) -> Result<String, WmError>;
+
+    /// Sets the connection and read/write timeouts, both in milliseconds. Transports that have no
+    /// notion of timeout can ignore it.
+    fn set_timeouts(&mut self, _conn_timeout: u64, _rw_timeout: u64) {}
+}
+
+/// Returns the value of the User-Agent header used by this client API when talking to the WM server.
+pub fn wm_client_user_agent() -> String {
+    format!("rust-wmclient-api-{}", WM_CLIENT_API_VERSION)
+}
+
+/// Default transport, based on the blocking `reqwest` HTTP client.
+pub struct ReqwestTransport {
+    _http_client: reqwest::blocking::Client,
+}
+
+impl ReqwestTransport {
+    /// Creates a new reqwest based transport using the default connection and read/write timeouts
+    pub fn new() -> Result<ReqwestTransport, WmError> {
+        ReqwestTransport::with_timeouts(DEFAULT_CONN_TIMEOUT, DEFAULT_RW_TIMEOUT)
+    }
+
+    /// Creates a new reqwest based transport using the given connection and read/write timeouts in milliseconds
+    pub fn with_timeouts(conn_timeout: u64, rw_timeout: u64) -> Result<ReqwestTransport, WmError> {
+        let http_client = reqwest::blocking::Client::builder()
+            .connect_timeout(Duration::from_millis(conn_timeout))
+            .timeout(Duration::from_millis(rw_timeout))
+            .pool_max_idle_per_host(100)
+            .build()?;
+        Ok(ReqwestTransport { _http_client: http_client })
+    }
+}
+
+impl Transport for ReqwestTransport {
+    fn get(&self, url: &str) -> Result<String, WmError> {
+        let response = self._http_client.get(url)
+            .header("content-type", DEFAULT_CONTENT_TYPE)
+            .header("User-Agent", wm_client_user_agent())
+            .send()?;
+
+        match response.text() {
+            Ok(body) => Ok(body),
+            Err(err) => Err(WmError::new(format!("Unable to perform get for path {}. Error {}", url, err))),
+        }
+    }
+
+    fn post(&self, url: &str, body: &str) -> Result<String, WmError> {
+        let response = self._http_client.post(url)
+            .header("Content-type", DEFAULT_CONTENT_TYPE)
+            .header("User-Agent", wm_client_user_agent())
+            .body(body.to_string())
+            .send()?;
+
+        let body = response.text()?;
+        Ok(body)
+    }
+
+    fn set_timeouts(&mut self, conn_timeout: u64, rw_timeout: u64) {
+        if let Ok(transport) = ReqwestTransport::with_timeouts(conn_timeout, rw_timeout) {
+            self._http_client = transport._http_client;
+        }
+    }
+}
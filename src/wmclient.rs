@@ -11,10 +11,13 @@
  */
 pub const DEVICE_ID_CACHE_TYPE: &str = "dId-cache";
 pub const USERAGENT_CACHE_TYPE: &str = "ua-cache";
+const WM_CLIENT_API_VERSION: &str = "0.2.0";
 const DEFAULT_CONTENT_TYPE: &str = "application/json";
 // timeouts are in milliseconds
 const DEFAULT_CONN_TIMEOUT: u64 = 10000;
 const DEFAULT_RW_TIMEOUT: u64 = 60000;
+const DEFAULT_MAX_HEADER_VALUE_LENGTH: usize = 8192;
+const DEFAULT_MAX_HEADER_COUNT: usize = 100;
 
 /// Client that interacts with a WURFL Microservice server (be it a docker image or a AWS/Azure or GCP
 /// virtual machine.
@@ -37,17 +40,22 @@ pub struct WmClient {
     // Internal caches
     _cache: Option<Cache>,
     // Maps concat headers (mainly UA) -> JSONDeviceData
-    // Stores the result of time consuming call getAllMakeModel
-    _make_models: Mutex<Vec<JSONMakeModel>>,
-    // List of device manufacturers
-    _device_makes: Mutex<Vec<String>>,
-    _device_makes_map: Mutex<HashMap<String, Vec<JSONModelMktName>>>,
-    // Map that associates os name to JSONDeviceOsVersions objects
-    _device_os_versions_map: Mutex<HashMap<String, Vec<String>>>,
-    // List of all device OSes
-    _device_oses: Mutex<Vec<String>>,
-    _ltime: String,
-    _http_client: reqwest::blocking::Client,
+    // Stores the result of time consuming call getAllMakeModel, with the makes and models lists built from it
+    _makes: RwLock<Option<Arc<MakesSnapshot>>>,
+    // OS names and their versions
+    _oses: RwLock<Option<Arc<OsSnapshot>>>,
+    // Held while expired makes or OS data is downloaded again, so that only one thread does it
+    _makes_refresh: Mutex<()>,
+    _oses_refresh: Mutex<()>,
+    // Age after which enumeration data is downloaded again, None to keep it until refreshed or cleared
+    _enumerations_ttl: Option<Duration>,
+    _ltime: Mutex<String>,
+    // Issues the HTTP calls to the WM server endpoints
+    _transport: Box<dyn Transport>,
+    // Rules used to read the headers passed to lookup_headers
+    _header_policy: HeaderPolicy,
+    // Capability profiles registered with register_profile, by name
+    _profiles: HashMap<String, RegisteredProfile>,
 }
 
 impl WmClient {
@@ -58,24 +66,64 @@ impl WmClient {
     /// let client = WmClient::new("http", "localhost", "8080", "");
     /// ```
     pub fn new(scheme: &str, host: &str, port: &str, base_uri: &str) -> Result<WmClient, WmError> {
+        let transport = ReqwestTransport::new()?;
+        WmClient::with_transport(scheme, host, port, base_uri, Box::new(transport))
+    }
+
+    /// Creates a new instance of the WURFL microservice client that records every request/response exchanged with the
+    /// WM server to the fixture file at `fixture_path`. The file is truncated when the client is created.
+    /// The fixture can later be used to create a client with `new_replaying`.
+    /// Basic usage:
+    /// ```no_run
+    /// use wmclient::WmClient;
+    /// let client = WmClient::new_recording("http", "localhost", "8080", "", "wm_fixture.jsonl");
+    /// ```
+    pub fn new_recording(scheme: &str, host: &str, port: &str, base_uri: &str, fixture_path: &str) -> Result<WmClient, WmError> {
+        let transport = RecordingTransport::create(Box::new(ReqwestTransport::new()?), fixture_path)?;
+        WmClient::with_transport(scheme, host, port, base_uri, Box::new(transport))
+    }
+
+    /// Creates a new instance of the WURFL microservice client that serves every request from the fixture file at
+    /// `fixture_path`, previously written by a client created with `new_recording`. No network access is performed:
+    /// a request that has not been recorded returns a WmError.
+    /// Basic usage:
+    /// ```no_run
+    /// use wmclient::WmClient;
+    /// let client = WmClient::new_replaying("wm_fixture.jsonl");
+    /// ```
+    pub fn new_replaying(fixture_path: &str) -> Result<WmClient, WmError> {
+        let transport = ReplayTransport::open(fixture_path)?;
+        WmClient::with_transport("http", "localhost", "8080", "", Box::new(transport))
+    }
+
+    /// Creates a new instance of the WURFL microservice client that issues its HTTP calls through the given `transport`,
+    /// instead of the default reqwest based one.
+    /// Basic usage:
+    /// ```no_run
+    /// use wmclient::{ReqwestTransport, WmClient};
+    /// let transport = ReqwestTransport::with_timeouts(2000, 5000).unwrap();
+    /// let client = WmClient::with_transport("http", "localhost", "8080", "", Box::new(transport));
+    /// ```
+    pub fn with_transport(scheme: &str, host: &str, port: &str, base_uri: &str, transport: Box<dyn Transport>) -> Result<WmClient, WmError> {
+        let mut wm_client = WmClient::_create(scheme, host, port, base_uri, transport);
+        let info_res = wm_client.get_info();
+        if let Ok(info) = info_res {
+            wm_client._set_info(info);
+            Ok(wm_client)
+        } else {
+            Err(WmError::new("Unable to create WURFL Microservice client: unable to get info from WM server".to_string()))
+        }
+    }
+
+    // Creates a client that has not yet got the info from the WM server
+    fn _create(scheme: &str, host: &str, port: &str, base_uri: &str, transport: Box<dyn Transport>) -> WmClient {
         let st_cap = vec![];
         let req_st_cap = vec![];
         let req_v_cap = vec![];
         let v_cap = vec![];
         let i_h = vec![];
-        let mk_md = vec![];
-        let d_mk = vec![];
-        let d_mm = HashMap::new();
-        let d_ovm = HashMap::new();
-        let d_oses = vec![];
-
-        let http_client = reqwest::blocking::Client::builder()
-            .connect_timeout(Duration::from_millis(DEFAULT_CONN_TIMEOUT))
-            .timeout(Duration::from_millis(DEFAULT_RW_TIMEOUT))
-            .pool_max_idle_per_host(100)
-            .build()?;
-
-        let mut wm_client = WmClient {
+
+        WmClient {
             _scheme: scheme.to_string(),
             _host: host.to_string(),
             _port: port.to_string(),
@@ -86,53 +134,46 @@ impl WmClient {
             requested_virtual_caps: Some(req_v_cap),
             important_headers: i_h,
             _cache: None,
-            _make_models: Mutex::new(mk_md),
-            _device_makes: Mutex::new(d_mk),
-            _device_makes_map: Mutex::new(d_mm),
-            _device_os_versions_map: Mutex::new(d_ovm),
-            _device_oses: Mutex::new(d_oses),
-            _ltime: "0".to_string(),
-            _http_client: http_client,
-        };
-
-        let info_res = wm_client.get_info();
-        if let Ok(info) = info_res {
-            wm_client.important_headers = info.important_headers.clone();
-            wm_client.static_caps = info.static_caps.clone();
-            wm_client.static_caps.sort();
-            wm_client.virtual_caps = info.virtual_caps.clone();
-            wm_client.virtual_caps.sort();
-            wm_client._ltime = info.ltime;
-            Ok(wm_client)
-        } else {
-            Err(WmError { msg: "Unable to create WURFL Microservice client: unable to get info from WM server".to_string() })
+            _makes: RwLock::new(None),
+            _oses: RwLock::new(None),
+            _makes_refresh: Mutex::new(()),
+            _oses_refresh: Mutex::new(()),
+            _enumerations_ttl: None,
+            _ltime: Mutex::new("0".to_string()),
+            _transport: transport,
+            _header_policy: HeaderPolicy::default(),
+            _profiles: HashMap::new(),
         }
     }
 
+    // Sets the capabilities, important headers and WURFL data load time of the WM server
+    fn _set_info(&mut self, info: JSONInfoData) {
+        self.important_headers = info.important_headers.clone();
+        self.static_caps = info.static_caps.clone();
+        self.static_caps.sort();
+        self.virtual_caps = info.virtual_caps.clone();
+        self.virtual_caps.sort();
+        self._ltime = Mutex::new(info.ltime);
+    }
+
     /// Returns the version of this Rust client API
     pub fn get_api_version(&self) -> &str {
-        "0.2.0"
+        WM_CLIENT_API_VERSION
     }
 
     /// sets the overall HTTP timeout in milliseconds
     pub fn set_http_timeout(&mut self, conn_timeout: u64, rw_timeout: u64) {
-        let http_client = reqwest::blocking::Client::builder()
-            .connect_timeout(Duration::from_millis(conn_timeout))
-            .timeout(Duration::from_millis(rw_timeout))
-            .pool_max_idle_per_host(100)
-            .build();
-
-        if http_client.is_ok() {
-            self._http_client = http_client.unwrap();
-        }
+        self._transport.set_timeouts(conn_timeout, rw_timeout);
     }
 
     /// returns true if WURFL microservice exposes the static capability with name `cap_name`, false otherwise
+    #[allow(clippy::needless_return)]
     pub fn has_static_capability(&self, cap_name: &str) -> bool {
         return self.static_caps.contains(&cap_name.to_string());
     }
 
     /// returns true if WURFL microservice exposes the virtual capability with name `cap_name`, false otherwise
+    #[allow(clippy::needless_return)]
     pub fn has_virtual_capability(&self, vcap_name: &str) -> bool {
         return self.virtual_caps.contains(&vcap_name.to_string());
     }
@@ -151,197 +192,169 @@ impl WmClient {
     ///     println!("WURFL API version: {}", info.wurfl_api_version);
     ///     println!("WURFL file info: {}", info.wurfl_info);
     pub fn get_info(&self) -> Result<JSONInfoData, WmError> {
-        let url = self._create_url("/v2/getinfo/json");
-        let response = self._http_client.get(url.as_str())
-            .header("content-type", DEFAULT_CONTENT_TYPE)
-            .header("User-Agent", self.get_wm_client_user_agent())
-            .send()?;
-
-        let info_res = response.json::<JSONInfoData>();
-        if info_res.is_ok() {
-            let info = info_res.unwrap();
-            let result = Ok(info);
-            return result;
-        } else {
-            let serde_err = info_res.err();
-            if serde_err.is_some() {
-                return Err(WmError { msg: serde_err.unwrap().to_string() });
-            } else {
-                return Err(WmError { msg: "Unable to parse JSON response".to_string() });
-            }
-        }
+        let body = self._http_get("/v2/getinfo/json")?;
+        let info = serde_json::from_str::<JSONInfoData>(body.as_str())?;
+        Ok(info)
     }
 
     /// lookup_useragent - Searches WURFL device data using the given user-agent for detection.
     /// Passing an empty string as user-agent will return a "generic" device.
-    pub fn lookup_useragent(&mut self, user_agent: String) -> Result<JSONDeviceData, WmError> {
+    pub fn lookup_useragent(&self, user_agent: String) -> Result<JSONDeviceData, WmError> {
+        self._lookup_useragent(user_agent, None, self._cache.as_ref())
+    }
+
+    /// lookup_useragent_with - Same as `lookup_useragent`, returning the capabilities in `caps` instead of the client requested ones
+    pub fn lookup_useragent_with(&self, caps: &CapabilitySet, user_agent: String) -> Result<JSONDeviceData, WmError> {
+        self._lookup_useragent(user_agent, Some(caps), self._cache.as_ref())
+    }
+
+    fn _lookup_useragent(&self, user_agent: String, caps: Option<&CapabilitySet>, cache: Option<&Cache>) -> Result<JSONDeviceData, WmError> {
         let mut headers = HashMap::new();
         headers.insert("User-Agent".to_string(), user_agent);
-        let cache_key = self._get_user_agent_cache_key(headers.clone()).unwrap();
+        let cache_key = self._get_user_agent_cache_key(&headers, caps).unwrap();
 
         // First: cache lookup
-        if self._cache.is_some() {
-            let device_opt = self._cache.as_ref().unwrap().get(USERAGENT_CACHE_TYPE.to_string(), cache_key.clone());
-            if device_opt.is_some() {
-                let d = device_opt.unwrap();
+        if let Some(cache) = cache {
+            if let Some(d) = cache.get(USERAGENT_CACHE_TYPE.to_string(), cache_key.clone()) {
                 return Ok(d);
             }
         }
 
+        let (requested_caps, requested_vcaps) = self._requested_caps(caps);
+        let json_request = Request::new(Some(headers), requested_caps, requested_vcaps, None);
+        let device = self._internal_lookup(json_request, "/v2/lookupuseragent/json".to_string())?;
 
-        let json_request = Request::new(Some(headers.clone()),
-                                        self.requested_static_caps.clone(),
-                                        self.requested_virtual_caps.clone(), None);
-        let result = self._internal_lookup(json_request, "/v2/lookupuseragent/json".to_string());
-        if result.is_ok() {
-            let device = result.unwrap();
-
-            // check if server WURFL.xml has been updated and, if so, clear caches
-            self._clear_caches_if_needed(device.ltime.clone());
-            if self._cache.is_some() {
-                self._cache.as_ref().unwrap().put(USERAGENT_CACHE_TYPE.to_string(), cache_key, device.clone());
-            }
-            return Ok(device);
-        } else {
-            return Err(result.err().unwrap());
+        // check if server WURFL.xml has been updated and, if so, clear caches
+        self._clear_caches_if_needed(device.ltime.clone());
+        if let Some(cache) = cache {
+            cache.put(USERAGENT_CACHE_TYPE.to_string(), cache_key, device.clone());
         }
+        Ok(device)
     }
 
     /// lookup_device_id - Searches WURFL device data using its wurfl_id value.
     /// Passing an empty or not existing wurfl_id value will make client return a WmError
-    pub fn lookup_device_id(&mut self, device_id: String) -> Result<JSONDeviceData, WmError> {
+    pub fn lookup_device_id(&self, device_id: String) -> Result<JSONDeviceData, WmError> {
+        self._lookup_device_id(device_id, None, self._cache.as_ref())
+    }
+
+    /// lookup_device_id_with - Same as `lookup_device_id`, returning the capabilities in `caps` instead of the client requested ones
+    pub fn lookup_device_id_with(&self, caps: &CapabilitySet, device_id: String) -> Result<JSONDeviceData, WmError> {
+        self._lookup_device_id(device_id, Some(caps), self._cache.as_ref())
+    }
+
+    fn _lookup_device_id(&self, device_id: String, caps: Option<&CapabilitySet>, cache: Option<&Cache>) -> Result<JSONDeviceData, WmError> {
+        let cache_key = match caps {
+            Some(caps) => device_id.clone() + caps._cache_key().as_str(),
+            None => device_id.clone(),
+        };
 
         // First: cache lookup
-        if self._cache.is_some() {
-            let device_opt = self._cache.as_ref().unwrap().get(DEVICE_ID_CACHE_TYPE.to_string(), device_id.clone());
-            if device_opt.is_some() {
-                let device_ref = device_opt.unwrap();
-                let device = JSONDeviceData {
-                    capabilities: device_ref.capabilities.clone(),
-                    error: device_ref.error.clone(),
-                    mtime: device_ref.mtime,
-                    ltime: device_ref.ltime,
-                };
+        if let Some(cache) = cache {
+            if let Some(device) = cache.get(DEVICE_ID_CACHE_TYPE.to_string(), cache_key.clone()) {
                 return Ok(device);
             }
         }
 
-        let json_request = Request::new(None,
-                                        self.requested_static_caps.clone(),
-                                        self.requested_virtual_caps.clone(), Some(device_id.clone()));
-        let result = self._internal_lookup(json_request, "/v2/lookupdeviceid/json".to_string());
-        if result.is_ok() {
-            let device = result.unwrap();
+        let (requested_caps, requested_vcaps) = self._requested_caps(caps);
+        let json_request = Request::new(None, requested_caps, requested_vcaps, Some(device_id));
+        let device = self._internal_lookup(json_request, "/v2/lookupdeviceid/json".to_string())?;
 
-            // check if server WURFL.xml has been updated and, if so, clear caches
-            self._clear_caches_if_needed(device.ltime.clone());
+        // check if server WURFL.xml has been updated and, if so, clear caches
+        self._clear_caches_if_needed(device.ltime.clone());
 
-            if self._cache.is_some() {
-                self._cache.as_ref().unwrap().put(DEVICE_ID_CACHE_TYPE.to_string(), device_id, device.clone());
-            }
-            return Ok(device);
-        } else {
-            return Err(result.err().unwrap());
+        if let Some(cache) = cache {
+            cache.put(DEVICE_ID_CACHE_TYPE.to_string(), cache_key, device.clone());
         }
+        Ok(device)
     }
 
     /// lookup_headers - Performs a device detection based on HTTP request headers that can be passed in any data structures that implement the
     /// `IntoIterator` trait (for example: HashMap or Hyper framework HeaderMap.
-    pub fn lookup_headers<U, V, T: IntoIterator<Item=(U, V)>>(&mut self, in_headers: T) -> Result<JSONDeviceData, WmError> where
+    /// Header names are case insensitive; values of headers that appear more than once are folded in a single comma separated value.
+    /// Besides the server `important_headers`, the User-Agent client hints listed in `CLIENT_HINTS_HEADERS` are forwarded to the server.
+    /// Header values are read according to the client `HeaderPolicy` (see `set_header_policy`): with the default one, values that are
+    /// not valid UTF-8 are decoded lossily and oversized values are skipped.
+    pub fn lookup_headers<U, V, T: IntoIterator<Item=(U, V)>>(&self, in_headers: T) -> Result<JSONDeviceData, WmError> where
         U: ToString,
         V: AsRef<[u8]> {
-        let mut headers: HashMap<String, String> = HashMap::new();
-
-        // first: make all headers lowercase
-        let mut lower_key_map: HashMap<String, String> = HashMap::new();
-        for (key, value) in in_headers {
-            lower_key_map.insert(key.to_string().to_lowercase(), from_utf8(value.as_ref()).unwrap().to_string());
-        }
-
-        // copy important headers with the headers name properly cased.
-        let ihs = &self.important_headers;
-        for ih_name in ihs {
-            let h_value = lower_key_map.get(ih_name.to_lowercase().as_str());
-            if h_value.is_some() && !h_value.unwrap().is_empty() {
-                headers.insert(ih_name.to_string(), h_value.unwrap().to_string());
-            }
-        }
-        let cache_key = self._get_user_agent_cache_key(headers.clone()).unwrap();
-
-        // Create the request object
-        let mut request = Request::new(Some(headers), self.requested_static_caps.clone(), self.requested_virtual_caps.clone(), None);
+        self._lookup_headers(in_headers, None, self._cache.as_ref())
+    }
 
-        // Do a cache lookup
-        if self._cache.is_some() {
-            let device_opt = self._cache.as_ref().unwrap().get(USERAGENT_CACHE_TYPE.to_string(), cache_key.clone());
+    /// lookup_headers_with - Same as `lookup_headers`, returning the capabilities in `caps` instead of the client requested ones
+    pub fn lookup_headers_with<U, V, T: IntoIterator<Item=(U, V)>>(&self, caps: &CapabilitySet, in_headers: T) -> Result<JSONDeviceData, WmError> where
+        U: ToString,
+        V: AsRef<[u8]> {
+        self._lookup_headers(in_headers, Some(caps), self._cache.as_ref())
+    }
 
-            if device_opt.is_some() {
-                let d = device_opt.unwrap();
-                return Ok(d);
+    fn _lookup_headers<U, V, T: IntoIterator<Item=(U, V)>>(&self, in_headers: T, caps: Option<&CapabilitySet>, cache: Option<&Cache>) -> Result<JSONDeviceData, WmError> where
+        U: ToString,
+        V: AsRef<[u8]> {
+        let mut headers: HashMap<String, String> = HashMap::new();
+        let policy = self._header_policy;
+
+        // first: collect the values of the headers sent to the server, matching their names case insensitively.
+        // Values of the other headers are never decoded.
+        let h_names = self._lookup_header_names();
+        let lower_names: Vec<String> = h_names.iter().map(|n| n.to_lowercase()).collect();
+        let mut h_values: Vec<Vec<String>> = vec![vec![]; h_names.len()];
+        let mut count = 0;
+        for (key, value) in in_headers {
+            let lower_key = key.to_string().to_lowercase();
+            if let Some(index) = lower_names.iter().position(|n| *n == lower_key) {
+                match policy._decode(h_names[index], value.as_ref(), count)? {
+                    Some(h_value) if !h_value.is_empty() => h_values[index].push(h_value),
+                    _ => {}
+                }
+                count += 1;
             }
         }
 
-        request.requested_caps = self.requested_static_caps.clone();
-        request.requested_vcaps = self.requested_virtual_caps.clone();
-
-
-        let device_res = self._internal_lookup(request, "/v2/lookuprequest/json".to_string());
-        if device_res.is_ok() {
-            let device = device_res.unwrap();
-            // check if server WURFL.xml has been updated and, if so, clear caches
-            self._clear_caches_if_needed(self._ltime.clone());
-            if self._cache.is_some() {
-                self._cache.as_ref().unwrap().put(USERAGENT_CACHE_TYPE.to_string(), cache_key, device.clone());
+        // copy important headers and client hints with the headers name properly cased, folding repeated ones.
+        for (index, values) in h_values.iter().enumerate() {
+            let h_value = values.join(", ");
+            if !h_value.is_empty() {
+                headers.insert(h_names[index].to_string(), h_value);
             }
-            return Ok(device);
-        } else {
-            return Err(device_res.err().unwrap());
         }
+        self._lookup_collected_headers(headers, caps, cache)
     }
 
     /// Clear all the caches in this client
-    pub fn clear_caches(&mut self) {
+    #[allow(clippy::unnecessary_unwrap)]
+    pub fn clear_caches(&self) {
         // This one clears the caches that associates headers to devices and WURFL IDs to devices
         if self._cache.is_some() {
             self._cache.as_ref().unwrap().clear();
         }
-
-        // the following calls clear frequently used "enumeration fields" which is very time consuming
-        // to download every time
-        let mk_md_lock_res = self._make_models.lock();
-        if mk_md_lock_res.is_ok() {
-            let mut make_models = mk_md_lock_res.unwrap();
-            make_models.clear();
-        }
-
-        let dev_makes_lock_guard = self._device_makes.lock();
-        if dev_makes_lock_guard.is_ok() {
-            let mut device_makes = dev_makes_lock_guard.unwrap();
-            device_makes.clear();
-            let dev_makes_map_guard = self._device_makes_map.lock();
-            if dev_makes_map_guard.is_ok() {
-                dev_makes_map_guard.unwrap().clear();
+        for profile in self._profiles.values() {
+            if let Some(cache) = &profile.cache {
+                cache.clear();
             }
         }
 
-        let dev_os_lock_res = self._device_oses.lock();
-        if dev_os_lock_res.is_ok() {
-            let mut device_oses = dev_os_lock_res.unwrap();
-            device_oses.clear();
-        }
+        // the following calls clear frequently used "enumeration fields" which is very time consuming
+        // to download every time. Each snapshot is dropped as a whole: readers keep using the one they already got
+        self._clear_enumerations();
+    }
 
-        let os_ver_map_lock_res = self._device_os_versions_map.lock();
-        if os_ver_map_lock_res.is_ok() {
-            let mut os_ver_map = os_ver_map_lock_res.unwrap();
-            os_ver_map.clear();
-        }
+    /// Sets the rules used by `lookup_headers` to read header values (UTF-8 decoding, maximum value length and header count,
+    /// and what to do with headers over these limits)
+    pub fn set_header_policy(&mut self, policy: HeaderPolicy) {
+        self._header_policy = policy;
     }
 
     /// Sets the new cache size. Changing cache size will result in a cache purge.
     pub fn set_cache_size(&mut self, ua_max_entries: usize) {
         self._cache = Some(Cache::new(ua_max_entries));
+        // registered profiles have caches of the same size
+        for profile in self._profiles.values_mut() {
+            profile.cache = Some(Cache::new(ua_max_entries));
+        }
     }
 
+    #[allow(clippy::needless_return)]
     fn _create_url(&self, path: &str) -> String {
         if !self._base_uri.is_empty() {
             return format!("{}://{}:{}/{}{}", self._scheme.as_str(), self._host.as_str(), self._port.as_str(), self._base_uri.as_str(), path);
@@ -349,50 +362,90 @@ impl WmClient {
         return format!("{}://{}:{}{}", self._scheme.as_str(), self._host.as_str(), self._port.as_str(), path);
     }
 
-    fn _get_user_agent_cache_key(&self, headers: HashMap<String, String>) -> Option<String> {
+    // Names of the headers sent to the server in a lookup request: the server important headers, followed by the client hints
+    // the server does not already list as important.
+    fn _lookup_header_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.important_headers.iter().map(|h| h.as_str()).collect();
+        for ch_name in CLIENT_HINTS_HEADERS {
+            if !self.important_headers.iter().any(|h| h.eq_ignore_ascii_case(ch_name)) {
+                names.push(ch_name);
+            }
+        }
+        names
+    }
+
+    // Performs the lookup of headers already filtered to the ones in `_lookup_header_names`, with their canonical names
+    fn _lookup_collected_headers(&self, headers: HashMap<String, String>, caps: Option<&CapabilitySet>, cache: Option<&Cache>) -> Result<JSONDeviceData, WmError> {
+        let cache_key = self._get_user_agent_cache_key(&headers, caps).unwrap();
+
+        // Create the request object
+        let (requested_caps, requested_vcaps) = self._requested_caps(caps);
+        let request = Request::new(Some(headers), requested_caps, requested_vcaps, None);
+
+        // Do a cache lookup
+        if let Some(cache) = cache {
+            if let Some(d) = cache.get(USERAGENT_CACHE_TYPE.to_string(), cache_key.clone()) {
+                return Ok(d);
+            }
+        }
+
+        let device = self._internal_lookup(request, "/v2/lookuprequest/json".to_string())?;
+        // check if server WURFL.xml has been updated and, if so, clear caches
+        self._clear_caches_if_needed(device.ltime.clone());
+        if let Some(cache) = cache {
+            cache.put(USERAGENT_CACHE_TYPE.to_string(), cache_key, device.clone());
+        }
+        Ok(device)
+    }
+
+    // Capabilities requested to the server: the ones in `caps` if any, otherwise the client requested ones
+    fn _requested_caps(&self, caps: Option<&CapabilitySet>) -> (Option<Vec<String>>, Option<Vec<String>>) {
+        match caps {
+            Some(caps) => caps._requested(),
+            None => (self.requested_static_caps.clone(), self.requested_virtual_caps.clone()),
+        }
+    }
+
+    fn _get_user_agent_cache_key(&self, headers: &HashMap<String, String>, caps: Option<&CapabilitySet>) -> Option<String> {
         let mut key = String::new();
-        // Using important headers array preserves header name order
-        for hname in &self.important_headers {
+        // Using lookup header names array preserves header name order. Names and values are separated,
+        // so that different headers cannot produce the same key
+        for hname in self._lookup_header_names() {
             if !hname.is_empty() {
-                let h_val = headers.get(hname.as_str());
-                if h_val.is_some() {
-                    key = key + h_val.unwrap().as_str();
+                if let Some(h_val) = headers.get(hname) {
+                    key += hname;
+                    key.push('\0');
+                    key += h_val.as_str();
+                    key.push('\0');
                 }
             }
         }
         let digest = md5::compute(key);
         //let str_digest = format!("{:x}", digest);
         let str_digest = String::from_utf8_lossy(digest.as_ref());
-        return Some(str_digest.to_string());
+        match caps {
+            Some(caps) => Some(str_digest.to_string() + caps._cache_key().as_str()),
+            None => Some(str_digest.to_string()),
+        }
     }
 
-    fn get_wm_client_user_agent(&self) -> String {
-        let mut ua = String::new();
-        ua = ua + "rust-wmclient-api-" + self.get_api_version();
-        return ua;
+    // Performs a GET request and returns the response body as a JSON String that can be unmarshalled
+    fn _http_get(&self, path: &str) -> Result<String, WmError> {
+        let url = self._create_url(path);
+        self._transport.get(url.as_str())
     }
 
-    // Performs a GET request and returns the response body as a JSON String that can be unmarshalled
-    fn _internal_get(&self, endpoint: String) -> Result<String, WmError> {
-        let url = self._create_url(endpoint.as_str());
-        let response = self._http_client.get(url.as_str())
-            .header("content-type", DEFAULT_CONTENT_TYPE)
-            .header("User-Agent", self.get_wm_client_user_agent())
-            .send()?;
-
-        let result = response.text();
-        return if result.is_ok() {
-            Ok(result.unwrap())
-        } else {
-            let err = result.err().unwrap();
-            let msg = format!("Unable to perform get for path {}. Error {}", url, err.to_string());
-            Err(WmError { msg })
-        };
+    // Performs a POST request sending the given request as JSON and returns the response body as a JSON String.
+    fn _http_post(&self, path: &str, request: &Request) -> Result<String, WmError> {
+        let url = self._create_url(path);
+        let body = serde_json::to_string(request)?;
+        self._transport.post(url.as_str(), body.as_str())
     }
 
     /// set_requested_static_capabilities - set list of standard static capabilities to return with the detected device.
     /// A device struct returned by the client may have up to 500 capabilities. This method is used mainly to limit the returned static capabilities to the ones you
-    /// really need.
+    /// really need. Names unknown to the server are silently ignored: `try_set_requested_static_capabilities` reports them instead.
+    #[allow(clippy::len_zero)]
     pub fn set_requested_static_capabilities(&mut self, cap_list: Option<Vec<&str>>) {
         if cap_list.is_none() {
             self.requested_static_caps = None;
@@ -414,7 +467,8 @@ impl WmClient {
 
     /// set_requested_virtual_capabilities - set list of standard virtual capabilities to return with the detected device.
     /// A device struct returned by the client may have up to 500 capabilities. This method is used mainly to limit the returned virtual capabilities to the ones you
-    /// really need.
+    /// really need. Names unknown to the server are silently ignored: `try_set_requested_virtual_capabilities` reports them instead.
+    #[allow(clippy::len_zero)]
     pub fn set_requested_virtual_capabilities(&mut self, vcap_list: Option<Vec<&str>>) {
         if vcap_list.is_none() {
             self.requested_virtual_caps = None;
@@ -436,7 +490,8 @@ impl WmClient {
 
     /// set_requested_capabilities - set list of standard capabilities to return with the detected device.
     /// Using this method you don't have to know if the requested capability is either static or virtual, the method
-    /// assigns the capability to the set it belongs.
+    /// assigns the capability to the set it belongs. Names unknown to the server are silently ignored: `try_set_requested_capabilities`
+    /// reports them instead.
     pub fn set_requested_capabilities(&mut self, cap_list: Option<Vec<&str>>) {
         if cap_list.is_none() {
             self.requested_static_caps = None;
@@ -460,28 +515,58 @@ impl WmClient {
         self.clear_caches();
     }
 
-    fn _internal_lookup(&self, request: Request, path: String) -> Result<JSONDeviceData, WmError> {
-        let url = self._create_url(path.as_str());
+    /// try_set_requested_static_capabilities - validating version of `set_requested_static_capabilities`: if any name is not
+    /// a static capability of the server a `WmErrorKind::InvalidCapability` error, listing the unknown names with suggestions,
+    /// is returned and the requested capabilities are not changed.
+    pub fn try_set_requested_static_capabilities(&mut self, cap_list: Vec<&str>, parsing: CapabilityParsing) -> Result<(), WmError> {
+        let cap_names = self._validate_capabilities(cap_list, parsing, true, false)?;
+        self.requested_static_caps = Some(cap_names);
+        self.clear_caches();
+        Ok(())
+    }
 
-        let response = self._http_client.post(url.as_str())
-            .header("Content-type", DEFAULT_CONTENT_TYPE)
-            .header("User-Agent", self.get_wm_client_user_agent().as_str())
-            .json(&request)
-            .send()?;
+    /// try_set_requested_virtual_capabilities - validating version of `set_requested_virtual_capabilities`,
+    /// see `try_set_requested_static_capabilities`.
+    pub fn try_set_requested_virtual_capabilities(&mut self, vcap_list: Vec<&str>, parsing: CapabilityParsing) -> Result<(), WmError> {
+        let vcap_names = self._validate_capabilities(vcap_list, parsing, false, true)?;
+        self.requested_virtual_caps = Some(vcap_names);
+        self.clear_caches();
+        Ok(())
+    }
+
+    /// try_set_requested_capabilities - validating version of `set_requested_capabilities`,
+    /// see `try_set_requested_static_capabilities`.
+    pub fn try_set_requested_capabilities(&mut self, cap_list: Vec<&str>, parsing: CapabilityParsing) -> Result<(), WmError> {
+        let names = self._validate_capabilities(cap_list, parsing, true, true)?;
+        let (cap_names, vcap_names): (Vec<String>, Vec<String>) = names.into_iter().partition(|n| self.has_static_capability(n));
+        self.requested_static_caps = Some(cap_names);
+        self.requested_virtual_caps = Some(vcap_names);
+        self.clear_caches();
+        Ok(())
+    }
 
-        let device = response.json::<JSONDeviceData>()?;
+    fn _internal_lookup(&self, request: Request, path: String) -> Result<JSONDeviceData, WmError> {
+        let body = self._http_post(path.as_str(), &request)?;
+        let device = serde_json::from_str::<JSONDeviceData>(body.as_str())?;
         Ok(device)
     }
 
-    fn _clear_caches_if_needed(&mut self, ltime: String) {
-        if ltime.len() > 0 && self._ltime != ltime {
-            self._ltime = ltime.to_string();
-            self.clear_caches();
+    fn _clear_caches_if_needed(&self, ltime: String) {
+        if ltime.is_empty() {
+            return;
         }
+        if let Ok(mut current_ltime) = self._ltime.lock() {
+            if *current_ltime == ltime {
+                return;
+            }
+            *current_ltime = ltime;
+        }
+        self.clear_caches();
     }
 
     /// get_actual_cache_sizes returns the values of cache size. The first value being the device-id based cache, the second value being
     /// the size of the headers-based one
+    #[allow(clippy::unnecessary_unwrap, clippy::needless_return)]
     pub fn get_actual_cache_sizes(&self) -> (usize, usize) {
         if self._cache.is_some() {
             return self._cache.as_ref().unwrap().get_actual_sizes();
@@ -491,89 +576,39 @@ impl WmClient {
 
     /// get_all_oses returns a vec<String> of all devices device_os capabilities in WM server
     pub fn get_all_oses(&self) -> Result<Vec<String>, WmError> {
-        let os_data = self._load_device_os_data();
-        if os_data.is_some() {
-            let wm_err = os_data.unwrap();
-            return Err(wm_err);
-        }
-
-        let os_guard = self._device_oses.lock();
-        if os_guard.is_ok() {
-            let vec = os_guard.unwrap();
-            let ret_val = vec.to_vec();
-            return Ok(ret_val);
-        } else {
-            let guard_err = os_guard.err().unwrap();
-            return Err(WmError { msg: format!("Cannot retrieve device OS list: {}", guard_err.to_string()) });
-        }
+        Ok(self._os_snapshot()?.device_oses.clone())
     }
 
-    /// Return a Vec<String> containing all the versions for the given `os_name`.
+    /// Return a Vec<String> containing all the versions for the given `os_name`, sorted as `OsVersion` (oldest first).
+    /// `os_name` is matched ignoring case and punctuation, see `find_versions_for_os`.
     /// It returns a WmError i case the given `os_name` does not exist
     pub fn get_all_versions_for_os(&self, os_name: &str) -> Result<Vec<String>, WmError> {
-        let mut os_versions: Vec<String> = Vec::new();
-        let os_data = self._load_device_os_data();
-        if os_data.is_some() {
-            let wm_err = os_data.unwrap();
-            return Err(wm_err);
-        }
-
-        let os_ver_map_guard = self._device_os_versions_map.lock();
-        if os_ver_map_guard.is_ok() {
-            let os_ver_map = os_ver_map_guard.unwrap();
-            if os_ver_map.contains_key(os_name) {
-                let os_vers_from_map = os_ver_map.get(os_name).unwrap();
-                for val in os_vers_from_map {
-                    if "" != val.as_str() {
-                        os_versions.push(val.to_string());
-                    }
-                }
-                os_versions.sort();
-                Ok(os_versions)
-            } else {
-                Err(WmError { msg: format!("Error getting data from WM server: {} does not exist or has no versions", os_name) })
-            }
+        let snapshot = self._os_snapshot()?;
+        let os_name = snapshot.os_names.find(os_name, &OS_NAME_ALIASES).unwrap_or(os_name);
+        if let Some(os_vers_from_map) = snapshot.os_versions_map.get(os_name) {
+            let mut os_versions: Vec<String> = os_vers_from_map.iter().filter(|v| !v.is_empty()).cloned().collect();
+            // numeric-aware order: "9" comes before "10"
+            os_versions.sort_by_cached_key(|v| OsVersion::parse(v));
+            Ok(os_versions)
         } else {
-            let guard_err = os_ver_map_guard.err().unwrap();
-            Err(WmError { msg: format!("Cannot retrieve device OS versions list: {}", guard_err.to_string()) })
+            Err(WmError::new(format!("Error getting data from WM server: {} does not exist or has no versions", os_name)))
         }
     }
 
     /// Returns the list of all device manufacturers in WURFL Microservice
     pub fn get_all_device_makes(&self) -> Result<Vec<String>, WmError> {
-        let makes_data = self._load_device_makes_data();
-        if makes_data.is_some() {
-            let wm_err = makes_data.unwrap();
-            return Err(wm_err);
-        }
-
-        let guard = self._device_makes.lock();
-        if guard.is_ok() {
-            let vec = guard.unwrap();
-            let ret_val = vec.to_vec();
-            return Ok(ret_val);
-        } else {
-            let guard_err = guard.err().unwrap();
-            return Err(WmError { msg: format!("Cannot retrieve device makes list: {}", guard_err.to_string()) });
-        }
+        Ok(self._makes_snapshot()?.device_makes.clone())
     }
 
     /// Returns a list of structs that hold data about model a device and marketing names for the given `brand_name`.
+    /// `brand_name` is matched ignoring case and punctuation, see `find_devices_for_make`.
     /// The method returns a WmError in case the `brand_name` does not exist.
     pub fn get_all_devices_for_make(&self, brand_name: String) -> Result<Vec<JSONModelMktName>, WmError> {
-        let makes_data = self._load_device_makes_data();
-        if makes_data.is_some() {
-            let wm_err = makes_data.unwrap();
-            return Err(wm_err);
-        }
-
-        let guard = self._device_makes_map.lock();
-        if guard.is_ok() {
-            let device_makes_map = guard.unwrap();
-            let vec_opt = device_makes_map.get(brand_name.as_str());
-            if vec_opt.is_some() {
+        let snapshot = self._makes_snapshot()?;
+        let canonical_name = snapshot.make_names.find(&brand_name, &BRAND_NAME_ALIASES);
+        match snapshot.device_makes_map.get(canonical_name.unwrap_or(&brand_name)) {
+            Some(md_mk_vec) => {
                 let mut ret_vec: Vec<JSONModelMktName> = Vec::new();
-                let md_mk_vec = vec_opt.unwrap();
                 for md_mk in md_mk_vec {
                     let md_mk_copy = JSONModelMktName {
                         model_name: md_mk.model_name.to_string(),
@@ -581,167 +616,10 @@ impl WmClient {
                     };
                     ret_vec.push(md_mk_copy);
                 }
-                return Ok(ret_vec);
-            } else {
-                return Err(WmError { msg: format!("Error getting data from WM server: brand {} does not exist or has no devices", brand_name) });
-            }
-        } else {
-            let guard_err = guard.err();
-            return Err(WmError { msg: format!("Error getting data from WM server: {}", guard_err.unwrap().to_string()) });
-        }
-    }
-
-    fn _load_device_os_data(&self) -> Option<WmError> {
-        let os_guard = self._device_oses.lock();
-        if os_guard.is_ok() {
-            let os_vec = os_guard.unwrap();
-            if !os_vec.is_empty() {
-                return None;
-            }
-        } else {
-            let err_msg = os_guard.as_ref().err().unwrap();
-            return Some(WmError { msg: err_msg.to_string() });
-        }
-
-        // this struct is a vector holding pairs of os name ("Android") and version ("10.0")
-        let mut os_version_pairs: Vec<JSONDeviceOsVersions> = Vec::with_capacity(1000);
-
-        let result = self.internal_get("/v2/alldeviceosversions/json");
-        match result {
-            Ok(res) => {
-                let res_string = res.text();
-                if res_string.is_ok() {
-                    let os_vers_str = res_string.unwrap();
-                    let _res: Result<Vec<JSONDeviceOsVersions>, serde_json::Error> = serde_json::from_str(os_vers_str.as_str());
-                    if _res.is_ok() {
-                        os_version_pairs = _res.unwrap();
-                    }
-                }
-            } // If we are here, something went wrong during download
-            Err(wm_err) => {
-                return Some(wm_err);
+                Ok(ret_vec)
             }
+            None => Err(WmError::new(format!("Error getting data from WM server: brand {} does not exist or has no devices", brand_name))),
         }
-
-        // If we are here, data download succeeded, now let's create the data structure that we'll use to return OS and version enumerations.
-        // this is a map that binds each OS name to a vector of their versions
-        let mut ov_map: HashMap<String, Vec<String>> = HashMap::new();
-
-        // we'll now check if an OS name has already been added to this map,
-        // if not, we'll create a vector to hold the OS versions and add it to the map together with its OS name as key.
-        // If OS name exists in the map, we just get the vector to which is associated and just add the os version value to the vector.
-        // Version number are guaranteed to be unique for each OS name.
-        for ov_item in os_version_pairs {
-            if !ov_map.contains_key(ov_item.device_os.as_str()) {
-                let mut ov: Vec<String> = Vec::new();
-                ov.push(ov_item.device_os_version.clone());
-                ov_map.insert(ov_item.device_os, ov.clone());
-            } else {
-                // we need to the get the vec as mutable to add an item to it
-                let ov_vec_opt = ov_map.get_mut(ov_item.device_os.as_str());
-                let ov_vec = ov_vec_opt.unwrap();
-                ov_vec.push(ov_item.device_os_version);
-            }
-        }
-
-        // we now use the keys of the map (all OSes) to fill the OSes vector
-        let dev_os_guard = self._device_oses.lock();
-        let mut os_vec = dev_os_guard.unwrap();
-        os_vec.clear();
-        let keys = ov_map.keys();
-        for k in keys {
-            os_vec.push(k.to_string());
-        }
-
-        // fill the wm client field with the results of the previous process
-        let dev_os_ver_map_guard = self._device_os_versions_map.lock();
-        let mut dev_os_ver_map = dev_os_ver_map_guard.unwrap();
-        dev_os_ver_map.clear();
-        dev_os_ver_map.extend(ov_map);
-        return None;
-    }
-
-    fn internal_get(&self, path: &str) -> Result<Response, WmError> {
-        let full_url = self._create_url(path);
-        let response = self._http_client.get(full_url.as_str())
-            .header("content-type", DEFAULT_CONTENT_TYPE)
-            .header("content-type", self.get_wm_client_user_agent())
-            .send()?;
-        Ok(response)
-    }
-
-    fn _load_device_makes_data(&self) -> Option<WmError> {
-        // We lock the shared makeModel cache
-        let dev_makes_guard = self._device_makes.lock();
-        if !dev_makes_guard.is_ok() {
-            let err = dev_makes_guard.err().unwrap();
-            return Some(WmError { msg: format!("Cannot download device makes data: {}", err.to_string()) });
-        } else {
-            let dev_makes = dev_makes_guard.unwrap();
-            if dev_makes.len() > 0 {
-                // cache has already been loaded or refreshed, return None
-                return None;
-            }
-        }
-
-        let mk_models: Vec<JSONMakeModel>;
-        let all_devices_res = self.internal_get("/v2/alldevices/json");
-        match all_devices_res {
-            Ok(res) => {
-                let res_string = res.text();
-                if res_string.is_ok() {
-                    let _res: Result<Vec<JSONMakeModel>, serde_json::Error> = serde_json::from_str(res_string.unwrap().as_str());
-                    if _res.is_ok() {
-                        mk_models = _res.unwrap();
-                    } else {
-                        return Some(WmError { msg: format!("Could not parse device makes data {} ", _res.err().unwrap().to_string()) });
-                    }
-                } else {
-                    let err = res_string.err();
-                    return Some(WmError { msg: format!("Could not parse device makes data {} ", err.unwrap().to_string()) });
-                }
-            }
-            Err(wm_err) => {
-                return Some(wm_err);
-            }
-        }
-
-        let mut dev_makes_map: HashMap<String, Vec<JSONModelMktName>> = HashMap::new();
-        for make_model in mk_models {
-            let mut marketing_name = "".to_string();
-            if make_model.marketing_name.is_some() {
-                marketing_name = make_model.marketing_name.unwrap();
-            }
-
-
-            let md_mk_name = JSONModelMktName {
-                model_name: make_model.model_name.to_string(),
-                marketing_name,
-            };
-            if !dev_makes_map.contains_key(make_model.brand_name.as_str()) {
-                let mut model_market_names: Vec<JSONModelMktName> = Vec::new();
-                model_market_names.push(md_mk_name);
-                dev_makes_map.insert(make_model.brand_name, model_market_names);
-            } else {
-                let model_market_names_opt = dev_makes_map.get_mut(make_model.brand_name.as_str());
-                let model_market_vec = model_market_names_opt.unwrap();
-                model_market_vec.push(md_mk_name);
-            }
-        }
-        let dev_makes_guard = self._device_makes.lock();
-        let mut dev_makes_vec = dev_makes_guard.unwrap();
-        dev_makes_vec.clear();
-        let keys = dev_makes_map.keys();
-        for k in keys {
-            dev_makes_vec.push(k.to_string());
-        }
-        // fill the wm client field with the results of the previous process
-        let dev_make_model_map_guard = self._device_makes_map.lock();
-        // We can unwrap safely since we know we created it.
-        let mut dev_make_model_map = dev_make_model_map_guard.unwrap();
-        dev_make_model_map.clear();
-        dev_make_model_map.extend(dev_makes_map);
-        return None;
     }
 }
 
@@ -0,0 +1,96 @@
+#![cfg(feature = "actix")]
+mod common;
+
+use actix_web::{test, web, App, HttpResponse};
+use common::*;
+use wmclient::*;
+
+async fn device_name(detected: DetectedDevice) -> HttpResponse {
+    HttpResponse::Ok().body(detected.device.capabilities.get("wurfl_id").unwrap().to_string())
+}
+
+#[actix_web::test]
+async fn middleware_stores_device_test() {
+    let (client, mock) = create_async_mock_client();
+    let app = test::init_service(App::new()
+        .wrap(WmDetection::new(client))
+        .route("/", web::get().to(device_name))).await;
+    let req = test::TestRequest::get().uri("/").insert_header(("User-Agent", SAMSUNG_UA)).to_request();
+    let body = test::call_and_read_body(&app, req).await;
+    assert_eq!("samsung_sm_g960f_ver1", body);
+    assert_eq!(1, mock.calls.posts().len());
+}
+
+#[actix_web::test]
+async fn extractor_without_middleware_test() {
+    let (client, mock) = create_async_mock_client();
+    let app = test::init_service(App::new()
+        .app_data(web::Data::new(client))
+        .route("/", web::get().to(device_name))).await;
+    let req = test::TestRequest::get().uri("/").insert_header(("User-Agent", IPHONE_UA)).to_request();
+    assert_eq!("apple_iphone_ver10_2_1", test::call_and_read_body(&app, req).await);
+
+    *mock.down.lock().unwrap() = true;
+    let req = test::TestRequest::get().uri("/").insert_header(("User-Agent", IPHONE_UA)).to_request();
+    let response = test::call_service(&app, req).await;
+    assert_eq!(503, response.status().as_u16());
+}
+
+#[actix_web::test]
+async fn middleware_failure_test() {
+    let (client, mock) = create_async_mock_client();
+    *mock.down.lock().unwrap() = true;
+    let app = test::init_service(App::new()
+        .app_data(web::Data::new(client.clone()))
+        .wrap(WmDetection::new(client))
+        .route("/", web::get().to(device_name))).await;
+    let req = test::TestRequest::get().uri("/").insert_header(("User-Agent", IPHONE_UA)).to_request();
+    let response = test::call_service(&app, req).await;
+    assert_eq!(503, response.status().as_u16());
+    // the extractor rejects with the middleware error instead of looking the headers up again
+    assert_eq!(1, mock.calls.posts().len());
+}
+
+#[actix_web::test]
+async fn extractor_without_client_test() {
+    let app = test::init_service(App::new().route("/", web::get().to(device_name))).await;
+    let req = test::TestRequest::get().uri("/").insert_header(("User-Agent", IPHONE_UA)).to_request();
+    assert_eq!(500, test::call_service(&app, req).await.status().as_u16());
+}
+
+#[actix_web::test]
+async fn middleware_response_headers_test() {
+    let (client, _) = create_async_mock_client();
+    let hints = ClientHints::with_hints(vec![SEC_CH_UA_PLATFORM], vec![SEC_CH_UA_MODEL]);
+    let app = test::init_service(App::new()
+        .wrap(WmDetection::new(client.clone()).vary().client_hints(hints))
+        .route("/", web::get().to(device_name))).await;
+    let req = test::TestRequest::get().uri("/").insert_header(("User-Agent", SWITCH_UA)).to_request();
+    let response = test::call_service(&app, req).await;
+    let headers = response.headers();
+    assert_eq!("User-Agent, Sec-CH-UA-Platform, Sec-CH-UA-Model", headers.get("Vary").unwrap());
+    assert_eq!("Sec-CH-UA-Platform, Sec-CH-UA-Model", headers.get("Accept-CH").unwrap());
+    assert_eq!("Sec-CH-UA-Model", headers.get("Critical-CH").unwrap());
+
+    // by default responses are not changed
+    let app = test::init_service(App::new()
+        .wrap(WmDetection::new(client))
+        .route("/", web::get().to(device_name))).await;
+    let req = test::TestRequest::get().uri("/").insert_header(("User-Agent", SWITCH_UA)).to_request();
+    let response = test::call_service(&app, req).await;
+    assert!(response.headers().get("Vary").is_none());
+    assert!(response.headers().get("Accept-CH").is_none());
+}
+
+#[actix_web::test]
+async fn middleware_merges_vary_test() {
+    let (client, _) = create_async_mock_client();
+    let hints = ClientHints::with_hints(vec![SEC_CH_UA_PLATFORM], vec![]);
+    let app = test::init_service(App::new()
+        .wrap(WmDetection::new(client).vary().client_hints(hints))
+        .route("/", web::get().to(|| async { HttpResponse::Ok().insert_header(("Vary", "Accept-Encoding, user-agent")).finish() }))).await;
+    let req = test::TestRequest::get().uri("/").insert_header(("User-Agent", SWITCH_UA)).to_request();
+    let response = test::call_service(&app, req).await;
+    let vary: Vec<_> = response.headers().get_all("Vary").collect();
+    assert_eq!(vec!["Accept-Encoding, user-agent, Sec-CH-UA-Platform"], vary);
+}
@@ -0,0 +1,98 @@
+#![cfg(all(feature = "axum", feature = "tower"))]
+mod common;
+
+use axum::body::{to_bytes, Body};
+use axum::extract::FromRef;
+use axum::http::{Request, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use common::*;
+use tower::ServiceExt;
+use wmclient::*;
+
+async fn device_name(detected: DetectedDevice) -> String {
+    detected.device.capabilities.get("wurfl_id").unwrap().to_string()
+}
+
+async fn call(app: Router, user_agent: &str) -> (StatusCode, String) {
+    let req = Request::builder().uri("/").header("User-Agent", user_agent).body(Body::empty()).unwrap();
+    let response = app.oneshot(req).await.unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, String::from_utf8(body.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn extractor_with_client_state_test() {
+    let (client, mock) = create_async_mock_client();
+    let app = Router::new().route("/", get(device_name)).with_state(client);
+    assert_eq!((StatusCode::OK, "samsung_sm_g960f_ver1".to_string()), call(app, SAMSUNG_UA).await);
+    assert_eq!(1, mock.calls.posts().len());
+}
+
+#[derive(Clone)]
+struct AppState {
+    wm_client: AsyncWmClient,
+}
+
+impl FromRef<AppState> for AsyncWmClient {
+    fn from_ref(state: &AppState) -> Self {
+        state.wm_client.clone()
+    }
+}
+
+#[tokio::test]
+async fn extractor_with_app_state_test() {
+    let (client, _) = create_async_mock_client();
+    let app = Router::new().route("/", get(device_name)).with_state(AppState { wm_client: client });
+    assert_eq!((StatusCode::OK, "google_bot".to_string()), call(app, BOT_UA).await);
+}
+
+#[tokio::test]
+async fn extractor_uses_layer_device_test() {
+    let (client, mock) = create_async_mock_client();
+    let app = Router::new().route("/", get(device_name))
+        .layer(WmDetectionLayer::new(client.clone()))
+        .with_state(client);
+    assert_eq!((StatusCode::OK, "apple_iphone_ver10_2_1".to_string()), call(app, IPHONE_UA).await);
+    // the device detected by the layer is reused
+    assert_eq!(1, mock.calls.posts().len());
+}
+
+#[tokio::test]
+async fn extractor_unreachable_server_test() {
+    let (client, mock) = create_async_mock_client();
+    *mock.down.lock().unwrap() = true;
+    let app = Router::new().route("/", get(device_name)).with_state(client);
+    let (status, body) = call(app, SAMSUNG_UA).await;
+    assert_eq!(StatusCode::SERVICE_UNAVAILABLE, status);
+    assert!(body.contains("connection refused"));
+}
+
+struct TeapotRejection;
+
+impl From<WmError> for TeapotRejection {
+    fn from(_: WmError) -> Self {
+        TeapotRejection
+    }
+}
+
+impl IntoResponse for TeapotRejection {
+    fn into_response(self) -> Response {
+        (StatusCode::IM_A_TEAPOT, "no device").into_response()
+    }
+}
+
+async fn device_name_with(detected: DetectedDeviceWith<TeapotRejection>) -> String {
+    detected.device.capabilities.get("wurfl_id").unwrap().to_string()
+}
+
+#[tokio::test]
+async fn extractor_custom_rejection_test() {
+    let (client, mock) = create_async_mock_client();
+    let app = Router::new().route("/", get(device_name_with)).with_state(client);
+    assert_eq!((StatusCode::OK, "nintendo_switch_ver1".to_string()), call(app.clone(), SWITCH_UA).await);
+    *mock.down.lock().unwrap() = true;
+    assert_eq!((StatusCode::IM_A_TEAPOT, "no device".to_string()), call(app, SWITCH_UA).await);
+}
@@ -5,6 +5,7 @@ use std::thread;
 
 use wmclient::{Cache, DEVICE_ID_CACHE_TYPE, JSONDeviceData, USERAGENT_CACHE_TYPE};
 
+#[allow(clippy::redundant_static_lifetimes)]
 const USER_AGENTS: &'static [&'static str] = &[
     "5.0 (Macintosh; Intel Mac OS X 10_11_6) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/55.0.2883.95 Safari/537.36 ASXACT12779",
     "97718A_ABROAD/V1 Linux/3.4.5 Android/4.2.2 Release/05.02.2013 Browser/AppleWebKit534.30 Mobile Safari/534.30 MBBMS/2.2;",
@@ -0,0 +1,149 @@
+mod common;
+
+use common::*;
+use wmclient::*;
+
+fn sorted_names(device: &JSONDeviceData) -> Vec<&str> {
+    let mut names: Vec<&str> = device.capabilities.keys().map(|k| k.as_str()).collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn capability_set_test() {
+    let (client, _) = create_mock_client();
+    let caps = CapabilitySet::new(&client, vec!["is_smartphone", "model_name", "brand_name", "not_a_cap", "model_name"]);
+    assert_eq!(vec!["brand_name", "model_name"], caps.static_caps());
+    assert_eq!(vec!["is_smartphone"], caps.virtual_caps());
+    assert!(!caps.is_empty());
+    // the order capabilities are listed in does not matter
+    assert_eq!(caps, CapabilitySet::new(&client, vec!["brand_name", "is_smartphone", "model_name"]));
+    assert!(CapabilitySet::new(&client, vec!["not_a_cap"]).is_empty());
+}
+
+#[test]
+fn lookup_with_caps_test() {
+    let (client, calls) = create_mock_client();
+    let caps = CapabilitySet::new(&client, vec!["brand_name", "form_factor"]);
+
+    let device = client.lookup_useragent_with(&caps, SAMSUNG_UA.to_string()).unwrap();
+    assert_eq!(vec!["brand_name", "form_factor", "wurfl_id"], sorted_names(&device));
+    let request = calls.last_post();
+    assert_eq!("brand_name", request["requested_caps"][0].as_str().unwrap());
+    assert_eq!("form_factor", request["requested_vcaps"][0].as_str().unwrap());
+
+    let device = client.lookup_device_id_with(&caps, "nintendo_switch_ver1".to_string()).unwrap();
+    assert_eq!(vec!["brand_name", "form_factor", "wurfl_id"], sorted_names(&device));
+
+    let device = client.lookup_headers_with(&caps, vec![("User-Agent", IPHONE_UA)]).unwrap();
+    assert_eq!(vec!["brand_name", "form_factor", "wurfl_id"], sorted_names(&device));
+
+    // client requested capabilities are not changed
+    let device = client.lookup_useragent(SAMSUNG_UA.to_string()).unwrap();
+    assert!(device.capabilities.contains_key("model_name"));
+    assert!(calls.last_post()["requested_caps"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn lookup_with_caps_overrides_client_caps_test() {
+    let (mut client, calls) = create_mock_client();
+    client.set_requested_capabilities(Some(vec!["model_name"]));
+    let caps = CapabilitySet::new(&client, vec!["is_ios"]);
+    let device = client.lookup_useragent_with(&caps, IPHONE_UA.to_string()).unwrap();
+    assert_eq!(vec!["is_ios", "wurfl_id"], sorted_names(&device));
+    assert_eq!(0, calls.last_post()["requested_caps"].as_array().unwrap().len());
+
+    // an empty set requests all the capabilities
+    let empty = CapabilitySet::new(&client, vec![]);
+    let device = client.lookup_useragent_with(&empty, IPHONE_UA.to_string()).unwrap();
+    assert!(device.capabilities.contains_key("model_name"));
+    assert!(device.capabilities.contains_key("is_ios"));
+}
+
+#[test]
+fn lookup_with_caps_cache_test() {
+    let (mut client, calls) = create_mock_client();
+    client.set_cache_size(100);
+    let small = CapabilitySet::new(&client, vec!["brand_name"]);
+    let large = CapabilitySet::new(&client, vec!["brand_name", "model_name", "is_android"]);
+
+    // each set has its own cache entries
+    let d1 = client.lookup_useragent_with(&small, SAMSUNG_UA.to_string()).unwrap();
+    let d2 = client.lookup_useragent_with(&large, SAMSUNG_UA.to_string()).unwrap();
+    let d3 = client.lookup_useragent(SAMSUNG_UA.to_string()).unwrap();
+    assert_eq!(2, d1.capabilities.len());
+    assert_eq!(4, d2.capabilities.len());
+    assert!(d3.capabilities.len() > 4);
+    assert_eq!(3, calls.posts().len());
+
+    // all of them are served from cache afterwards
+    assert_eq!(d1.capabilities, client.lookup_useragent_with(&small, SAMSUNG_UA.to_string()).unwrap().capabilities);
+    assert_eq!(d2.capabilities, client.lookup_headers_with(&large, vec![("User-Agent", SAMSUNG_UA)]).unwrap().capabilities);
+    assert_eq!(d3.capabilities, client.lookup_useragent(SAMSUNG_UA.to_string()).unwrap().capabilities);
+    assert_eq!(3, calls.posts().len());
+    assert_eq!(3, client.get_actual_cache_sizes().1);
+
+    client.lookup_device_id_with(&small, "google_bot".to_string()).unwrap();
+    client.lookup_device_id_with(&large, "google_bot".to_string()).unwrap();
+    client.lookup_device_id_with(&small, "google_bot".to_string()).unwrap();
+    assert_eq!(5, calls.posts().len());
+    assert_eq!(2, client.get_actual_cache_sizes().0);
+}
+
+#[test]
+fn try_set_requested_capabilities_test() {
+    let (mut client, calls) = create_mock_client();
+    assert!(client.try_set_requested_capabilities(vec!["brand_name", "is_ios"], CapabilityParsing::Strict).is_ok());
+    client.lookup_useragent(IPHONE_UA.to_string()).unwrap();
+    let request = calls.last_post();
+    assert_eq!("brand_name", request["requested_caps"][0].as_str().unwrap());
+    assert_eq!("is_ios", request["requested_vcaps"][0].as_str().unwrap());
+
+    let err = client.try_set_requested_capabilities(vec!["brand_name", "modle_name", "is_smrtphone", "xyz"], CapabilityParsing::Strict).err().unwrap();
+    assert_eq!(WmErrorKind::InvalidCapability, err.kind);
+    assert_eq!("Unknown capabilities: modle_name (did you mean model_name?), is_smrtphone (did you mean is_smartphone?), xyz", err.msg);
+    // requested capabilities are left unchanged
+    client.lookup_useragent(IPHONE_UA.to_string()).unwrap();
+    assert_eq!(1, calls.last_post()["requested_caps"].as_array().unwrap().len());
+}
+
+#[test]
+fn try_set_requested_static_and_virtual_capabilities_test() {
+    let (mut client, calls) = create_mock_client();
+    let err = client.try_set_requested_static_capabilities(vec!["model_name", "form_factor"], CapabilityParsing::Strict).err().unwrap();
+    assert_eq!("Unknown capabilities: form_factor (is a virtual capability)", err.msg);
+    let err = client.try_set_requested_virtual_capabilities(vec!["brand_name"], CapabilityParsing::Strict).err().unwrap();
+    assert_eq!("Unknown capabilities: brand_name (is a static capability)", err.msg);
+
+    client.try_set_requested_static_capabilities(vec!["model_name"], CapabilityParsing::Strict).unwrap();
+    client.try_set_requested_virtual_capabilities(vec!["form_factor"], CapabilityParsing::Strict).unwrap();
+    let device = client.lookup_useragent(SAMSUNG_UA.to_string()).unwrap();
+    assert_eq!(vec!["form_factor", "model_name", "wurfl_id"], sorted_names(&device));
+    assert_eq!(1, calls.last_post()["requested_vcaps"].as_array().unwrap().len());
+}
+
+#[test]
+fn whitespace_separated_capabilities_test() {
+    let (mut client, _) = create_mock_client();
+    // a whitespace separated list is an error in strict mode...
+    let err = client.try_set_requested_static_capabilities(vec!["model_name brand_name"], CapabilityParsing::Strict).err().unwrap();
+    assert_eq!("Unknown capabilities: \"model_name brand_name\" (did you mean to pass model_name, brand_name as separate names?)", err.msg);
+
+    // ...and is split in lenient mode
+    client.try_set_requested_static_capabilities(vec!["model_name brand_name", " device_os\t"], CapabilityParsing::Lenient).unwrap();
+    let device = client.lookup_useragent(SAMSUNG_UA.to_string()).unwrap();
+    assert_eq!(vec!["brand_name", "device_os", "model_name", "wurfl_id"], sorted_names(&device));
+
+    let err = client.try_set_requested_capabilities(vec!["model_name brnd_name"], CapabilityParsing::Lenient).err().unwrap();
+    assert_eq!("Unknown capabilities: brnd_name (did you mean brand_name?)", err.msg);
+}
+
+#[test]
+fn capability_set_try_new_test() {
+    let (client, _) = create_mock_client();
+    let caps = CapabilitySet::try_new(&client, vec!["is_android form_factor", "brand_name"], CapabilityParsing::Lenient).unwrap();
+    assert_eq!(vec!["brand_name"], caps.static_caps());
+    assert_eq!(vec!["form_factor", "is_android"], caps.virtual_caps());
+    let err = CapabilitySet::try_new(&client, vec!["resolution_widht"], CapabilityParsing::Strict).err().unwrap();
+    assert_eq!("Unknown capabilities: resolution_widht (did you mean resolution_width?)", err.msg);
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use common::*;
+use wmclient::*;
+
+#[test]
+fn embedded_registry_test() {
+    let registry = CapabilityRegistry::embedded();
+    assert!(registry.len() > 50);
+    let form_factor = registry.get("form_factor").unwrap();
+    assert_eq!(CapabilityType::Enum, form_factor.value_type);
+    assert!(form_factor.is_virtual);
+    assert!(form_factor.enum_values.contains(&"Smartphone".to_string()));
+    let width = registry.get("resolution_width").unwrap();
+    assert_eq!(CapabilityType::Int, width.value_type);
+    assert_eq!("display", width.group);
+    assert!(!width.is_virtual);
+    assert!(registry.get("not_a_capability").is_none());
+
+    assert!(registry.groups().contains(&"product_info"));
+    assert!(registry.in_group("display").iter().all(|i| i.group == "display"));
+    let names: Vec<&str> = registry.iter().map(|i| i.name.as_str()).collect();
+    let mut sorted = names.clone();
+    sorted.sort();
+    assert_eq!(sorted, names);
+    assert!(registry.iter().all(|i| !i.description.is_empty()));
+    assert!(registry.iter().all(|i| i.enum_values.is_empty() != (i.value_type == CapabilityType::Enum)));
+}
+
+#[test]
+fn parse_capability_values_test() {
+    let registry = CapabilityRegistry::embedded();
+    let is_tablet = registry.get("is_tablet").unwrap();
+    assert_eq!(CapabilityValue::Bool(true), is_tablet.parse("true").unwrap());
+    assert_eq!(Some(false), is_tablet.parse("false").unwrap().as_bool());
+    let err = is_tablet.parse("yes").unwrap_err();
+    assert_eq!(WmErrorKind::InvalidValue, err.kind);
+    assert_eq!("Invalid value \"yes\" for bool capability is_tablet", err.msg);
+    assert_eq!(Some(1440), registry.get("resolution_width").unwrap().parse("1440").unwrap().as_int());
+    assert!(registry.get("resolution_width").unwrap().parse("wide").is_err());
+    assert_eq!(Some("Tablet"), registry.get("form_factor").unwrap().parse("Tablet").unwrap().as_str());
+    assert_eq!(Some("Other non-Mobile"), registry.get("form_factor").unwrap().parse("Other non-Mobile").unwrap().as_str());
+    // values missing from the enum list are not errors
+    assert_eq!(CapabilityValue::String("Toaster".to_string()), registry.get("form_factor").unwrap().parse("Toaster").unwrap());
+    assert_eq!(CapabilityValue::String(String::new()), registry.get("marketing_name").unwrap().parse("").unwrap());
+    assert_eq!("1440", CapabilityValue::Int(1440).to_string());
+
+    // a registry built from custom metadata, where the last duplicate wins
+    let info = |value_type: CapabilityType| CapabilityInfo {
+        name: "custom".to_string(), value_type, group: "custom".to_string(), is_virtual: false, enum_values: vec![],
+        description: "A custom capability".to_string(),
+    };
+    let registry = CapabilityRegistry::new(vec![info(CapabilityType::String), info(CapabilityType::Int)]);
+    assert_eq!(1, registry.len());
+    assert_eq!(CapabilityType::Int, registry.get("custom").unwrap().value_type);
+}
+
+#[test]
+fn registry_check_test() {
+    let (client, _) = create_mock_client();
+    let check = client.check_capability_registry().unwrap();
+    assert!(check.is_consistent());
+    assert!(check.unknown.is_empty());
+    assert!(check.missing.contains(&"physical_screen_width".to_string()));
+    assert!(!check.missing.contains(&"brand_name".to_string()));
+
+    let mut info = client.get_info().unwrap();
+    info.static_caps.push("my_capability".to_string());
+    info.static_caps.push("is_robot".to_string());
+    info.virtual_caps.retain(|c| c != "is_robot");
+    let check = CapabilityRegistry::embedded().check(&info);
+    assert!(!check.is_consistent());
+    assert_eq!(vec!["my_capability"], check.unknown);
+    assert_eq!(vec!["is_robot"], check.wrong_kind);
+    assert!(!check.missing.contains(&"is_robot".to_string()));
+
+    assert_eq!(CapabilityType::Bool, client.capability_info("is_smartphone").unwrap().value_type);
+    // known by the registry, not exposed by the server
+    assert!(client.capability_info("physical_screen_width").is_none());
+}
+
+#[test]
+fn typed_device_capabilities_test() {
+    let (client, _) = create_mock_client();
+    let device = client.lookup_useragent(SAMSUNG_UA.to_string()).unwrap();
+    assert_eq!(CapabilityValue::Int(1440), device.typed_capability("resolution_width").unwrap());
+    assert_eq!(CapabilityValue::Enum("Smartphone".to_string()), device.typed_capability("form_factor").unwrap());
+    let device_switch = client.lookup_useragent(SWITCH_UA.to_string()).unwrap();
+    assert_eq!(CapabilityValue::Enum("Other non-Mobile".to_string()), device_switch.typed_capability("form_factor").unwrap());
+    assert_eq!(Some(true), device.typed_capability("is_android").unwrap().as_bool());
+    // not in the registry
+    assert_eq!(CapabilityValue::String("samsung_sm_g960f_ver1".to_string()), device.typed_capability("wurfl_id").unwrap());
+    assert_eq!(WmErrorKind::InvalidCapability, device.typed_capability("physical_screen_width").unwrap_err().kind);
+
+    for ua in [SAMSUNG_UA, IPHONE_UA, SWITCH_UA, BOT_UA] {
+        assert!(client.lookup_useragent(ua.to_string()).unwrap().validate_capabilities().is_ok());
+    }
+    let mut device = device;
+    device.capabilities.insert("is_tablet".to_string(), "maybe".to_string());
+    device.capabilities.insert("resolution_width".to_string(), "".to_string());
+    let err = device.validate_capabilities().unwrap_err();
+    assert_eq!(WmErrorKind::InvalidValue, err.kind);
+    assert_eq!("Invalid value \"\" for int capability resolution_width; Invalid value \"maybe\" for bool capability is_tablet", err.msg);
+}
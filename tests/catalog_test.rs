@@ -0,0 +1,67 @@
+mod common;
+
+use common::*;
+use wmclient::*;
+
+fn models<'a>(devices: impl IntoIterator<Item=&'a MakeModel>) -> Vec<&'a str> {
+    devices.into_iter().map(|d| d.model_name.as_str()).collect()
+}
+
+#[test]
+fn catalog_from_client_test() {
+    let (client, calls) = create_mock_client();
+    let catalog = client.get_device_catalog().unwrap();
+    assert_eq!(9, catalog.len());
+    assert_eq!(vec!["Apple", "Nintendo", "Nokia", "Samsung"], catalog.brands());
+    let iphone = catalog.find_by_model("iphone");
+    assert_eq!(1, iphone.len());
+    assert_eq!(None, iphone[0].marketing_name);
+    // the catalog and the make enumerations share the same download
+    client.get_all_device_makes().unwrap();
+    client.get_device_catalog().unwrap();
+    assert_eq!(1, calls.count("/v2/alldevices/json"));
+}
+
+#[test]
+fn catalog_case_insensitive_lookups_test() {
+    let (client, _) = create_mock_client();
+    let catalog = client.get_device_catalog().unwrap();
+    assert_eq!(Some("Samsung"), catalog.find_brand("SAMSUNG"));
+    assert_eq!(None, catalog.find_brand("Motorola"));
+    assert_eq!(vec!["iPad Pro", "iPhone", "iPhone 12"], models(catalog.devices_for_brand(" apple ")));
+    assert_eq!(vec!["SM-G960F"], models(catalog.find_by_model("sm g960f")));
+    // reverse lookup from marketing name
+    assert_eq!(vec!["SM-G960F", "SM-G960U"], models(catalog.find_by_marketing_name("GALAXY s9")));
+    assert_eq!(vec!["SM-G965F"], models(catalog.find_by_marketing_name("Galaxy S9+")));
+    assert!(catalog.find_by_marketing_name("").is_empty());
+}
+
+#[test]
+fn catalog_search_test() {
+    let (client, _) = create_mock_client();
+    let catalog = client.get_device_catalog().unwrap();
+
+    assert_eq!(vec!["SM-G960F", "SM-G960U", "SM-G965F"], models(catalog.search_prefix("galaxy s9")));
+    assert_eq!(vec!["iPhone", "iPhone 12"], models(catalog.search_prefix("Apple iPh")));
+    assert_eq!(vec!["3310"], models(catalog.search_prefix("nok")));
+
+    let matches = catalog.search("galaxy s9", 10);
+    assert_eq!(vec!["SM-G960F", "SM-G960U", "SM-G965F"], models(matches.iter().map(|m| m.device)));
+    assert_eq!(1.0, matches[0].score);
+    assert!(matches[2].score < matches[1].score);
+    assert_eq!(2, catalog.search("galaxy s9", 2).len());
+
+    // model name prefix and typos
+    assert_eq!(vec!["SM-G960F", "SM-G960U"], models(catalog.search("SM-G960", 10).iter().map(|m| m.device)));
+    assert_eq!("SM-T870", catalog.search("samsung galxy tab", 10)[0].device.model_name);
+    assert!(catalog.search("motorola razr", 10).is_empty());
+    assert!(catalog.search("", 10).is_empty());
+}
+
+#[test]
+fn catalog_new_test() {
+    let device = |brand: &str, model: &str| MakeModel { brand_name: brand.to_string(), model_name: model.to_string(), marketing_name: None };
+    let catalog = DeviceCatalog::new(vec![device("B", "2"), device("A", "1"), device("B", "2")]);
+    assert_eq!(vec![device("A", "1"), device("B", "2")], catalog.devices().to_vec());
+    assert!(DeviceCatalog::new(vec![]).is_empty());
+}
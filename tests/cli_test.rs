@@ -0,0 +1,198 @@
+#![cfg(feature = "cli")]
+
+mod common;
+
+use std::env;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+use common::*;
+use wmclient::*;
+
+// Records, against the mock server, the exchanges done by the command line tool in the tests below.
+fn record_fixture(name: &str) -> PathBuf {
+    let path = env::temp_dir().join(name);
+    let recording = RecordingTransport::create(Box::new(MockTransport::new()), path.to_str().unwrap()).unwrap();
+    let client = WmClient::with_transport("http", "localhost", "8080", "", Box::new(recording)).unwrap();
+    client.lookup_useragent(SAMSUNG_UA.to_string()).unwrap();
+    client.lookup_device_id("nintendo_switch_ver1".to_string()).unwrap();
+    client.lookup_headers(vec![("User-Agent".to_string(), IPHONE_UA.to_string()), ("Accept".to_string(), "text/html".to_string())]).unwrap();
+    client.get_all_devices_for_make("Samsung".to_string()).unwrap();
+    client.get_all_versions_for_os("iOS").unwrap();
+    let stats_caps = CapabilitySet::new(&client, STATISTICS_CAPABILITIES.to_vec());
+    for ua in [SAMSUNG_UA, IPHONE_UA, BOT_UA] {
+        client.lookup_useragent_with(&stats_caps, ua.to_string()).unwrap();
+    }
+    path
+}
+
+fn run_cli(fixture: &PathBuf, args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_wmclient"))
+        .arg("--replay").arg(fixture)
+        .args(args)
+        .stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped())
+        .spawn().unwrap();
+    child.stdin.take().unwrap().write_all(stdin.as_bytes()).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn cli_lookups_test() {
+    let fixture = record_fixture("wmclient_cli_lookups_test.jsonl");
+
+    let out = stdout(&run_cli(&fixture, &["ua", SAMSUNG_UA], ""));
+    assert!(out.lines().any(|l| l.starts_with("wurfl_id") && l.ends_with("samsung_sm_g960f_ver1")));
+
+    let out = stdout(&run_cli(&fixture, &["--json", "id", "nintendo_switch_ver1"], ""));
+    let device: serde_json::Value = serde_json::from_str(&out).unwrap();
+    assert_eq!("Nintendo", device["capabilities"]["brand_name"]);
+
+    // raw HTTP request read from stdin
+    let request = format!("GET / HTTP/1.1\r\nUser-Agent: {}\r\nAccept: text/html\r\n\r\nbody", IPHONE_UA);
+    let out = stdout(&run_cli(&fixture, &["headers"], &request));
+    assert!(out.contains("apple_iphone_ver10_2_1"));
+    // same headers as a JSON object
+    let request = serde_json::json!({"User-Agent": IPHONE_UA, "Accept": ["text/html"]}).to_string();
+    let out = stdout(&run_cli(&fixture, &["headers"], &request));
+    assert!(out.contains("apple_iphone_ver10_2_1"));
+}
+
+#[test]
+fn cli_enumerations_test() {
+    let fixture = record_fixture("wmclient_cli_enumerations_test.jsonl");
+
+    let out = stdout(&run_cli(&fixture, &["makes"], ""));
+    assert_eq!(vec!["Apple", "Nintendo", "Nokia", "Samsung"], out.lines().collect::<Vec<&str>>());
+
+    let out = stdout(&run_cli(&fixture, &["--json", "models", "Samsung"], ""));
+    let models: Vec<JSONModelMktName> = serde_json::from_str(&out).unwrap();
+    assert_eq!(4, models.len());
+
+    let out = stdout(&run_cli(&fixture, &["versions", "iOS"], ""));
+    assert_eq!(vec!["9.3", "10.2.1", "14.0"], out.lines().collect::<Vec<&str>>());
+
+    let out = stdout(&run_cli(&fixture, &["--json", "info"], ""));
+    let info: serde_json::Value = serde_json::from_str(&out).unwrap();
+    assert_eq!("2.1.0", info["wm_version"]);
+}
+
+#[test]
+fn cli_enrich_test() {
+    let fixture = record_fixture("wmclient_cli_enrich_test.jsonl");
+
+    let log = format!("id,user_agent\n1,\"{}\"\n2,\"{}\"\n", SAMSUNG_UA, SAMSUNG_UA);
+    let output = run_cli(&fixture, &["enrich", "--format", "csv"], &log);
+    let out = stdout(&output);
+    let mut reader = csv::Reader::from_reader(out.as_bytes());
+    let wurfl_id = reader.headers().unwrap().iter().position(|h| h == "wurfl_id").unwrap();
+    let rows: Vec<csv::StringRecord> = reader.records().map(|r| r.unwrap()).collect();
+    assert_eq!(2, rows.len());
+    assert_eq!(Some("samsung_sm_g960f_ver1"), rows[1].get(wurfl_id));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("2 records, 2 enriched"));
+}
+
+#[test]
+fn cli_stats_test() {
+    let fixture = record_fixture("wmclient_cli_stats_test.jsonl");
+
+    let user_agents = format!("{}\n{}\n\n{}\n{}\n", SAMSUNG_UA, IPHONE_UA, BOT_UA, SAMSUNG_UA);
+    let out = stdout(&run_cli(&fixture, &["--csv", "stats"], &user_agents));
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!("Dimension,Value,Count,Percentage", lines[0]);
+    assert!(lines.contains(&"form_factor,Smartphone,3,75.00"));
+    assert!(lines.contains(&"traffic,bot,1,25.00"));
+
+    let out = stdout(&run_cli(&fixture, &["--json", "stats"], &user_agents));
+    let report: StatisticsReport = serde_json::from_str(&out).unwrap();
+    assert_eq!(4, report.total);
+    assert_eq!("Samsung", report.brand_name[0].value);
+}
+
+#[test]
+fn cli_snapshot_and_diff_test() {
+    let fixture = record_fixture("wmclient_cli_snapshot_test.jsonl");
+    let (client, _) = create_mock_client();
+    let mut snapshot = client.get_enumeration_snapshot().unwrap();
+    let old_path = env::temp_dir().join("wmclient_cli_snapshot_old.json");
+    snapshot.write(std::fs::File::create(&old_path).unwrap()).unwrap();
+    snapshot.make_models.retain(|m| m.brand_name != "Nokia");
+    snapshot.os_versions.get_mut("Android").unwrap().push("12".to_string());
+    let new_path = env::temp_dir().join("wmclient_cli_snapshot_new.json");
+    snapshot.write(std::fs::File::create(&new_path).unwrap()).unwrap();
+
+    let out = stdout(&run_cli(&fixture, &["--csv", "diff", old_path.to_str().unwrap(), new_path.to_str().unwrap()], ""));
+    assert_eq!(vec!["Change,Kind,Value", "-,brand,Nokia", "-,model,Nokia 3310", "+,os version,Android 12"], out.lines().collect::<Vec<&str>>());
+    let out = stdout(&run_cli(&fixture, &["--json", "diff", new_path.to_str().unwrap(), old_path.to_str().unwrap()], ""));
+    let diff: EnumerationDiff = serde_json::from_str(&out).unwrap();
+    assert_eq!(vec!["Nokia"], diff.brands.added);
+}
+
+// The mock server after a data update that changed the Samsung screen width
+struct UpdatedTransport {
+    inner: MockTransport,
+}
+
+impl Transport for UpdatedTransport {
+    fn get(&self, url: &str) -> Result<String, WmError> {
+        self.inner.get(url)
+    }
+
+    fn post(&self, url: &str, body: &str) -> Result<String, WmError> {
+        Ok(self.inner.post(url, body)?.replace(r#""resolution_width":"1440""#, r#""resolution_width":"1080""#))
+    }
+}
+
+fn record_compare_fixture(name: &str, transport: Box<dyn Transport>) -> PathBuf {
+    let path = env::temp_dir().join(name);
+    let recording = RecordingTransport::create(transport, path.to_str().unwrap()).unwrap();
+    let client = WmClient::with_transport("http", "localhost", "8080", "", Box::new(recording)).unwrap();
+    for ua in [SAMSUNG_UA, IPHONE_UA] {
+        client.lookup_useragent(ua.to_string()).unwrap();
+    }
+    path
+}
+
+#[test]
+fn cli_compare_test() {
+    let old = record_compare_fixture("wmclient_cli_compare_old.jsonl", Box::new(MockTransport::new()));
+    let new = record_compare_fixture("wmclient_cli_compare_new.jsonl", Box::new(UpdatedTransport { inner: MockTransport::new() }));
+    let user_agents = format!("{}\n\n{}\n", SAMSUNG_UA, IPHONE_UA);
+
+    let output = run_cli(&old, &["--csv", "compare", "--against-replay", new.to_str().unwrap()], &user_agents);
+    let out = stdout(&output);
+    assert_eq!(vec!["User-Agent,Change,Capability,Old value,New value".to_string(), format!("\"{}\",~,resolution_width,1440,1080", SAMSUNG_UA)],
+               out.lines().collect::<Vec<&str>>());
+    assert_eq!("2 User-Agents, 1 with differences, 0 lookup errors\n", String::from_utf8_lossy(&output.stderr));
+
+    let out = stdout(&run_cli(&new, &["--json", "compare", "--against-replay", old.to_str().unwrap()], &user_agents));
+    let diffs: serde_json::Value = serde_json::from_str(&out).unwrap();
+    assert_eq!(SAMSUNG_UA, diffs[0]["user_agent"]);
+    assert_eq!("1080", diffs[0]["diff"]["changed"][0]["old_value"]);
+    assert_eq!(1, diffs.as_array().unwrap().len());
+
+    // one of --against and --against-replay is required
+    assert!(!run_cli(&old, &["compare"], "").status.success());
+}
+
+#[test]
+fn cli_errors_test() {
+    let fixture = record_fixture("wmclient_cli_errors_test.jsonl");
+
+    let output = run_cli(&fixture, &["headers"], "User-Agent Mozilla\r\n");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Invalid header at line 1"));
+
+    let output = run_cli(&fixture, &["--caps", "brand_nam", "makes"], "");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("did you mean brand_name?"));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_wmclient")).args(["--url", "localhost:8080", "info"]).output().unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Invalid server URL"));
+}
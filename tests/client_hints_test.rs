@@ -0,0 +1,119 @@
+mod common;
+
+use common::*;
+use wmclient::*;
+
+const CH_UA: &str = "\"Chromium\";v=\"110\", \"Not A(Brand\";v=\"24\", \"Google Chrome\";v=\"110\"";
+const REDUCED_UA: &str = "Mozilla/5.0 (Linux; Android 10; K) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/110.0.0.0 Mobile Safari/537.36";
+
+#[test]
+fn accept_ch_test() {
+    let hints = ClientHints::new();
+    assert_eq!("Sec-CH-UA, Sec-CH-UA-Mobile, Sec-CH-UA-Platform, Sec-CH-UA-Platform-Version, Sec-CH-UA-Model, Sec-CH-UA-Full-Version-List", hints.accept_ch());
+    assert_eq!("Sec-CH-UA-Platform-Version, Sec-CH-UA-Model, Sec-CH-UA-Full-Version-List", hints.critical_ch());
+
+    let headers = hints.response_headers();
+    assert_eq!(2, headers.len());
+    assert_eq!("Accept-CH", headers[0].0);
+    assert_eq!("Critical-CH", headers[1].0);
+}
+
+#[test]
+fn custom_accept_ch_test() {
+    // critical hints are accepted too, duplicates are removed regardless of their casing
+    let hints = ClientHints::with_hints(vec![SEC_CH_UA, "sec-ch-ua"], vec![SEC_CH_UA_MODEL]);
+    assert_eq!("Sec-CH-UA, Sec-CH-UA-Model", hints.accept_ch());
+    assert_eq!("Sec-CH-UA-Model", hints.critical_ch());
+
+    let no_critical = ClientHints::with_hints(vec![SEC_CH_UA_MOBILE], vec![]);
+    assert_eq!(1, no_critical.response_headers().len());
+}
+
+#[test]
+fn lookup_headers_forwards_client_hints_test() {
+    let (client, calls) = create_mock_client();
+    let headers = vec![
+        ("User-Agent", REDUCED_UA),
+        ("Sec-CH-UA", CH_UA),
+        ("Sec-CH-UA-Mobile", "?1"),
+        ("Sec-CH-UA-Platform", "\"Android\""),
+        ("Sec-CH-UA-Platform-Version", "\"10.0.0\""),
+        ("Sec-CH-UA-Model", "\"SM-G960F\""),
+        ("Sec-CH-UA-Full-Version-List", "\"Chromium\";v=\"110.0.5481.153\", \"Google Chrome\";v=\"110.0.5481.153\""),
+        ("Accept-Language", "en"),
+    ];
+    let device = client.lookup_headers(headers).unwrap();
+    // the model is only known through the Sec-CH-UA-Model hint
+    assert_eq!("SM-G960F", device.capabilities.get("model_name").unwrap().as_str());
+
+    let sent_headers = calls.last_post()["lookup_headers"].as_object().unwrap().clone();
+    assert_eq!(7, sent_headers.len());
+    assert_eq!("\"SM-G960F\"", sent_headers["Sec-CH-UA-Model"].as_str().unwrap());
+    assert_eq!(CH_UA, sent_headers["Sec-CH-UA"].as_str().unwrap());
+    assert_eq!("\"10.0.0\"", sent_headers["Sec-CH-UA-Platform-Version"].as_str().unwrap());
+    assert!(sent_headers.get("Accept-Language").is_none());
+}
+
+#[test]
+fn lookup_headers_client_hints_casing_test() {
+    let (client, calls) = create_mock_client();
+    let headers = vec![
+        ("user-agent", REDUCED_UA),
+        ("sec-ch-ua-model", "\"SM-G960F\""),
+        ("SEC-CH-UA-PLATFORM-VERSION", "\"10.0.0\""),
+        ("Sec-Ch-Ua-Full-Version-List", "\"Chromium\";v=\"110.0.5481.153\""),
+    ];
+    let _ = client.lookup_headers(headers).unwrap();
+
+    // headers are sent with their canonical names, whatever casing they were received with
+    let sent_headers = calls.last_post()["lookup_headers"].as_object().unwrap().clone();
+    let mut names: Vec<&String> = sent_headers.keys().collect();
+    names.sort();
+    assert_eq!(vec!["Sec-CH-UA-Full-Version-List", "Sec-CH-UA-Model", "Sec-CH-UA-Platform-Version", "User-Agent"], names);
+}
+
+#[test]
+fn lookup_headers_folding_test() {
+    let (client, calls) = create_mock_client();
+    // Sec-CH-UA split across two header lines, as allowed for list based header fields
+    let headers = vec![
+        ("User-Agent", REDUCED_UA),
+        ("Sec-CH-UA", "\"Chromium\";v=\"110\""),
+        ("sec-ch-ua", "\"Google Chrome\";v=\"110\""),
+    ];
+    let _ = client.lookup_headers(headers).unwrap();
+    let sent_headers = calls.last_post()["lookup_headers"].as_object().unwrap().clone();
+    assert_eq!("\"Chromium\";v=\"110\", \"Google Chrome\";v=\"110\"", sent_headers["Sec-CH-UA"].as_str().unwrap());
+}
+
+#[test]
+fn lookup_headers_client_hints_cache_key_test() {
+    let (mut client, calls) = create_mock_client();
+    client.set_cache_size(100);
+    let samsung = vec![("User-Agent", REDUCED_UA), ("Sec-CH-UA-Model", "\"SM-G960F\"")];
+    let other = vec![("User-Agent", REDUCED_UA), ("Sec-CH-UA-Model", "\"K\"")];
+
+    let d1 = client.lookup_headers(samsung.clone()).unwrap();
+    let d2 = client.lookup_headers(other).unwrap();
+    let d3 = client.lookup_headers(samsung).unwrap();
+    // same UA, different hints: they must not share the same cache entry
+    assert_eq!("SM-G960F", d1.capabilities.get("model_name").unwrap().as_str());
+    assert_eq!("generic", d2.capabilities.get("wurfl_id").unwrap().as_str());
+    assert_eq!("SM-G960F", d3.capabilities.get("model_name").unwrap().as_str());
+    assert_eq!(2, calls.posts().len());
+    assert_eq!(2, client.get_actual_cache_sizes().1);
+}
+
+#[test]
+fn lookup_headers_cache_key_separates_values_test() {
+    let (mut client, calls) = create_mock_client();
+    client.set_cache_size(100);
+    // concatenated without separators, both header sets would give the same cache key
+    let split = vec![("User-Agent", REDUCED_UA), ("Sec-CH-UA-Platform", "\"Android\""), ("Sec-CH-UA-Model", "\"SM-G960F\"")];
+    let joined = vec![("User-Agent", REDUCED_UA), ("Sec-CH-UA-Platform", "\"Android\"\"SM-G960F\"")];
+
+    client.lookup_headers(split).unwrap();
+    client.lookup_headers(joined).unwrap();
+    assert_eq!(2, calls.posts().len());
+    assert_eq!(2, client.get_actual_cache_sizes().1);
+}
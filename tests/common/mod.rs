@@ -0,0 +1,191 @@
+// In memory transport and data shared by the tests that run without a WURFL Microservice server.
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use wmclient::*;
+
+pub const INFO_JSON: &str = r#"{"wurfl_api_version":"1.12.0.0","wm_version":"2.1.0","wurfl_info":"/usr/share/wurfl/wurfl.zip:for WURFL API 1.12.0","important_headers":["User-Agent","X-UCBrowser-Device-UA","Device-Stock-UA","X-OperaMini-Phone-UA"],"static_caps":["brand_name","device_os","device_os_version","is_tablet","is_wireless_device","marketing_name","model_name","pointing_method","resolution_height","resolution_width"],"virtual_caps":["complete_device_name","form_factor","is_android","is_ios","is_robot","is_smartphone"],"ltime":"2021-09-01 10:00:00"}"#;
+
+pub const ALL_DEVICES_JSON: &str = r#"[
+{"brand_name":"Samsung","model_name":"SM-G960F","marketing_name":"Galaxy S9"},
+{"brand_name":"Samsung","model_name":"SM-G960U","marketing_name":"Galaxy S9"},
+{"brand_name":"Samsung","model_name":"SM-G965F","marketing_name":"Galaxy S9+"},
+{"brand_name":"Samsung","model_name":"SM-T870","marketing_name":"Galaxy Tab S7"},
+{"brand_name":"Apple","model_name":"iPhone","marketing_name":null},
+{"brand_name":"Apple","model_name":"iPhone 12","marketing_name":null},
+{"brand_name":"Apple","model_name":"iPad Pro","marketing_name":"iPad Pro 12.9"},
+{"brand_name":"Nokia","model_name":"3310","marketing_name":""},
+{"brand_name":"Nintendo","model_name":"Switch","marketing_name":null}
+]"#;
+
+pub const OS_VERSIONS_JSON: &str = r#"[
+{"device_os":"Android","device_os_version":"4.4"},
+{"device_os":"Android","device_os_version":"8.0"},
+{"device_os":"Android","device_os_version":"9"},
+{"device_os":"Android","device_os_version":"10"},
+{"device_os":"Android","device_os_version":"11"},
+{"device_os":"Android","device_os_version":""},
+{"device_os":"iOS","device_os_version":"9.3"},
+{"device_os":"iOS","device_os_version":"10.2.1"},
+{"device_os":"iOS","device_os_version":"14.0"},
+{"device_os":"Nokia OS","device_os_version":"1.0"}
+]"#;
+
+pub const SAMSUNG_UA: &str = "Mozilla/5.0 (Linux; Android 10; SM-G960F) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/83.0.4103.106 Mobile Safari/537.36";
+pub const IPHONE_UA: &str = "Mozilla/5.0 (iPhone; CPU iPhone OS 10_2_1 like Mac OS X) AppleWebKit/602.4.6 (KHTML, like Gecko) Version/10.0 Mobile/14D27 Safari/602.1";
+pub const SWITCH_UA: &str = "Mozilla/5.0 (Nintendo Switch; WebApplet) AppleWebKit/601.6 (KHTML, like Gecko) NF/4.0.0.5.9 NintendoBrowser/5.1.0.13341";
+pub const BOT_UA: &str = "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)";
+
+// (wurfl_id, token searched in the lookup headers, capabilities)
+type MockDevice = (&'static str, &'static str, Vec<(&'static str, &'static str)>);
+
+fn mock_devices() -> Vec<MockDevice> {
+    vec![
+        ("samsung_sm_g960f_ver1", "SM-G960F", vec![("brand_name", "Samsung"), ("model_name", "SM-G960F"), ("marketing_name", "Galaxy S9"),
+            ("form_factor", "Smartphone"), ("is_robot", "false"), ("is_smartphone", "true"), ("is_tablet", "false"),
+            ("device_os", "Android"), ("device_os_version", "10.0"), ("resolution_width", "1440"), ("is_android", "true"), ("is_ios", "false")]),
+        ("apple_iphone_ver10_2_1", "iPhone", vec![("brand_name", "Apple"), ("model_name", "iPhone"), ("marketing_name", ""),
+            ("form_factor", "Smartphone"), ("is_robot", "false"), ("is_smartphone", "true"), ("is_tablet", "false"),
+            ("device_os", "iOS"), ("device_os_version", "10.2.1"), ("resolution_width", "320"), ("is_android", "false"), ("is_ios", "true")]),
+        ("nintendo_switch_ver1", "Nintendo Switch", vec![("brand_name", "Nintendo"), ("model_name", "Switch"), ("marketing_name", ""),
+            ("form_factor", "Other non-Mobile"), ("is_robot", "false"), ("is_smartphone", "false"), ("is_tablet", "false"),
+            ("device_os", "Nintendo Switch OS"), ("device_os_version", ""), ("resolution_width", "1280"), ("is_android", "false"), ("is_ios", "false")]),
+        ("google_bot", "Googlebot", vec![("brand_name", "Google"), ("model_name", "Bot"), ("marketing_name", ""),
+            ("form_factor", "Robot"), ("is_robot", "true"), ("is_smartphone", "false"), ("is_tablet", "false"),
+            ("device_os", ""), ("device_os_version", ""), ("resolution_width", "800"), ("is_android", "false"), ("is_ios", "false")]),
+        ("generic", "", vec![("brand_name", "Generic"), ("model_name", ""), ("marketing_name", ""),
+            ("form_factor", "Feature Phone"), ("is_robot", "false"), ("is_smartphone", "false"), ("is_tablet", "false"),
+            ("device_os", ""), ("device_os_version", ""), ("resolution_width", "90"), ("is_android", "false"), ("is_ios", "false")]),
+    ]
+}
+
+/// A request received by the mock transport
+#[derive(Debug, Clone)]
+pub struct MockCall {
+    pub method: String,
+    pub url: String,
+    pub body: Option<serde_json::Value>,
+}
+
+/// Shared handle to the requests received by a MockTransport
+#[derive(Clone, Default)]
+pub struct MockCalls(Arc<Mutex<Vec<MockCall>>>);
+
+impl MockCalls {
+    pub fn all(&self) -> Vec<MockCall> {
+        self.0.lock().unwrap().clone()
+    }
+
+    pub fn posts(&self) -> Vec<serde_json::Value> {
+        self.all().into_iter().filter(|c| c.method == "POST").map(|c| c.body.unwrap()).collect()
+    }
+
+    pub fn last_post(&self) -> serde_json::Value {
+        self.posts().pop().unwrap()
+    }
+
+    pub fn count(&self, path: &str) -> usize {
+        self.all().iter().filter(|c| c.url.ends_with(path)).count()
+    }
+}
+
+/// Transport that answers like a WM server, using a few hardcoded devices
+#[derive(Clone)]
+pub struct MockTransport {
+    pub calls: MockCalls,
+    pub ltime: Arc<Mutex<String>>,
+    pub down: Arc<Mutex<bool>>,
+    // milliseconds every request takes to answer
+    pub delay: Arc<Mutex<u64>>,
+}
+
+impl MockTransport {
+    pub fn new() -> MockTransport {
+        MockTransport { calls: MockCalls::default(), ltime: Arc::new(Mutex::new("2021-09-01 10:00:00".to_string())), down: Arc::new(Mutex::new(false)), delay: Arc::new(Mutex::new(0)) }
+    }
+
+    fn _check_down(&self) -> Result<(), WmError> {
+        if *self.down.lock().unwrap() {
+            return Err(WmError::with_kind(WmErrorKind::Unreachable, "error sending request: connection refused".to_string()));
+        }
+        let delay = *self.delay.lock().unwrap();
+        if delay > 0 {
+            std::thread::sleep(std::time::Duration::from_millis(delay));
+        }
+        Ok(())
+    }
+}
+
+impl Transport for MockTransport {
+    fn get(&self, url: &str) -> Result<String, WmError> {
+        self.calls.0.lock().unwrap().push(MockCall { method: "GET".to_string(), url: url.to_string(), body: None });
+        self._check_down()?;
+        if url.ends_with("/v2/getinfo/json") {
+            return Ok(INFO_JSON.replace("2021-09-01 10:00:00", self.ltime.lock().unwrap().as_str()));
+        } else if url.ends_with("/v2/alldevices/json") {
+            return Ok(ALL_DEVICES_JSON.to_string());
+        } else if url.ends_with("/v2/alldeviceosversions/json") {
+            return Ok(OS_VERSIONS_JSON.to_string());
+        }
+        Err(WmError::new(format!("404 Not Found: {}", url)))
+    }
+
+    fn post(&self, url: &str, body: &str) -> Result<String, WmError> {
+        let request: serde_json::Value = serde_json::from_str(body).unwrap();
+        self.calls.0.lock().unwrap().push(MockCall { method: "POST".to_string(), url: url.to_string(), body: Some(request.clone()) });
+        self._check_down()?;
+
+        let devices = mock_devices();
+        let device = if let Some(wurfl_id) = request["wurfl_id"].as_str() {
+            match devices.into_iter().find(|d| d.0 == wurfl_id) {
+                Some(d) => d,
+                None => return Err(WmError::new(format!("device id {} not found", wurfl_id))),
+            }
+        } else {
+            let mut header_values = String::new();
+            if let Some(headers) = request["lookup_headers"].as_object() {
+                for value in headers.values() {
+                    header_values.push_str(value.as_str().unwrap());
+                    header_values.push(' ');
+                }
+            }
+            devices.into_iter().find(|d| d.1.is_empty() || header_values.contains(d.1)).unwrap()
+        };
+
+        // as the WM server does, return only the requested capabilities (plus wurfl_id) if any is requested
+        let mut requested: Vec<String> = vec![];
+        for list in ["requested_caps", "requested_vcaps"] {
+            if let Some(names) = request[list].as_array() {
+                requested.extend(names.iter().map(|n| n.as_str().unwrap().to_string()));
+            }
+        }
+        let mut capabilities: HashMap<String, String> = HashMap::new();
+        capabilities.insert("wurfl_id".to_string(), device.0.to_string());
+        for (name, value) in device.2 {
+            if requested.is_empty() || requested.contains(&name.to_string()) {
+                capabilities.insert(name.to_string(), value.to_string());
+            }
+        }
+        let device_data = JSONDeviceData { capabilities, error: "".to_string(), mtime: 1630490400, ltime: self.ltime.lock().unwrap().to_string() };
+        Ok(serde_json::to_string(&device_data).unwrap())
+    }
+}
+
+/// Creates a client backed by a MockTransport, returning the handle to the requests it receives
+pub fn create_mock_client() -> (WmClient, MockCalls) {
+    let transport = MockTransport::new();
+    let calls = transport.calls.clone();
+    let client = WmClient::with_transport("http", "localhost", "8080", "", Box::new(transport)).unwrap();
+    (client, calls)
+}
+
+/// Creates an async client backed by a MockTransport, returning the transport handle to inspect calls or simulate failures
+#[cfg(feature = "async")]
+pub fn create_async_mock_client() -> (AsyncWmClient, MockTransport) {
+    let transport = MockTransport::new();
+    let handle = transport.clone();
+    let client = WmClient::with_transport("http", "localhost", "8080", "", Box::new(transport)).unwrap();
+    (AsyncWmClient::new(client), handle)
+}
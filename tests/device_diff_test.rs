@@ -0,0 +1,48 @@
+mod common;
+
+use common::*;
+use wmclient::*;
+
+#[test]
+fn device_diff_between_test() {
+    let (client, _) = create_mock_client();
+    let samsung = client.lookup_useragent(SAMSUNG_UA.to_string()).unwrap();
+    assert!(DeviceDiff::between(&samsung, &samsung.clone()).is_empty());
+
+    let mut updated = samsung.clone();
+    updated.capabilities.insert("resolution_width".to_string(), "1080".to_string());
+    updated.capabilities.insert("is_foldable".to_string(), "false".to_string());
+    updated.capabilities.remove("marketing_name");
+    updated.ltime = "2024-01-01 10:00:00".to_string();
+    let diff = DeviceDiff::between(&samsung, &updated);
+    assert!(!diff.is_empty());
+    assert_eq!(vec![("is_foldable".to_string(), "false".to_string())], diff.added.into_iter().collect::<Vec<(String, String)>>());
+    assert_eq!(Some(&"Galaxy S9".to_string()), diff.removed.get("marketing_name"));
+    assert_eq!(1, diff.removed.len());
+    assert_eq!(vec![CapabilityChange { name: "resolution_width".to_string(), old_value: "1440".to_string(), new_value: "1080".to_string() }], diff.changed);
+}
+
+#[test]
+fn device_diff_other_device_test() {
+    let (client, _) = create_mock_client();
+    let samsung = client.lookup_useragent(SAMSUNG_UA.to_string()).unwrap();
+    let iphone = client.lookup_useragent(IPHONE_UA.to_string()).unwrap();
+    let diff = DeviceDiff::between(&samsung, &iphone);
+    assert!(diff.added.is_empty() && diff.removed.is_empty());
+    let wurfl_id = diff.change("wurfl_id").unwrap();
+    assert_eq!("samsung_sm_g960f_ver1", wurfl_id.old_value);
+    assert_eq!("apple_iphone_ver10_2_1", wurfl_id.new_value);
+    assert!(diff.change("form_factor").is_none());
+    let names: Vec<&str> = diff.changed.iter().map(|c| c.name.as_str()).collect();
+    let mut sorted = names.clone();
+    sorted.sort();
+    assert_eq!(sorted, names);
+
+    // fewer capabilities requested
+    let caps = CapabilitySet::new(&client, vec!["brand_name", "form_factor"]);
+    let partial = client.lookup_useragent_with(&caps, SAMSUNG_UA.to_string()).unwrap();
+    let diff = DeviceDiff::between(&samsung, &partial);
+    assert!(diff.added.is_empty() && diff.changed.is_empty());
+    assert!(diff.removed.contains_key("model_name"));
+    assert!(!diff.removed.contains_key("brand_name"));
+}
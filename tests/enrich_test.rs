@@ -0,0 +1,138 @@
+#![cfg(feature = "enrich")]
+
+mod common;
+
+use common::*;
+use wmclient::*;
+
+fn combined_line(user_agent: &str) -> String {
+    format!(r#"127.0.0.1 - - [10/Oct/2021:13:55:36 +0200] "GET /index.html HTTP/1.1" 200 2326 "https://example.com/" "{}""#, user_agent)
+}
+
+#[test]
+fn enrich_combined_log_test() {
+    let (mut client, calls) = create_mock_client();
+    client.set_cache_size(1000);
+    let input = format!("{}\n{}\n{}\nnot a log line\n", combined_line(SAMSUNG_UA), combined_line(IPHONE_UA), combined_line(SAMSUNG_UA));
+    let mut output = vec![];
+    let stats = LogEnricher::new(&client, LogFormat::Combined)
+        .capabilities(vec!["brand_name", "form_factor"])
+        .unwrap()
+        .enrich(input.as_bytes(), &mut output)
+        .unwrap();
+    assert_eq!(EnrichStats { records: 4, enriched: 3, skipped: 1, errors: 0 }, stats);
+
+    let output = String::from_utf8(output).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(4, lines.len());
+    assert_eq!(format!(r#"{} wurfl_id="samsung_sm_g960f_ver1" brand_name="Samsung" form_factor="Smartphone""#, combined_line(SAMSUNG_UA)), lines[0]);
+    assert!(lines[1].ends_with(r#"wurfl_id="apple_iphone_ver10_2_1" brand_name="Apple" form_factor="Smartphone""#));
+    assert_eq!("not a log line", lines[3]);
+    // the repeated User-Agent is served by the cache
+    assert_eq!(2, calls.count("/v2/lookupuseragent/json"));
+}
+
+#[test]
+fn enrich_json_lines_test() {
+    let (client, _) = create_mock_client();
+    let input = format!("{}\n{{\"path\":\"/\"}}\n", serde_json::json!({"path": "/", "ua": SWITCH_UA}));
+    let mut output = vec![];
+    let stats = LogEnricher::new(&client, LogFormat::JsonLines { ua_field: "ua".to_string() })
+        .capabilities(vec!["model_name"])
+        .unwrap()
+        .enrich(input.as_bytes(), &mut output)
+        .unwrap();
+    assert_eq!(EnrichStats { records: 2, enriched: 1, skipped: 1, errors: 0 }, stats);
+
+    let output = String::from_utf8(output).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+    let record: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+    assert_eq!("/", record["path"]);
+    assert_eq!(serde_json::json!({"wurfl_id": "nintendo_switch_ver1", "model_name": "Switch"}), record["wurfl"]);
+    assert_eq!(r#"{"path":"/"}"#, lines[1]);
+}
+
+#[test]
+fn enrich_json_lines_keeps_record_text_test() {
+    let (client, _) = create_mock_client();
+    let ua = serde_json::to_string(SWITCH_UA).unwrap();
+    let input = format!("{{\"z\": 1.50, \"ua\": {}, \"a\": \"\\u00e9\"}}\n{{\"ua\": {}, \"wurfl\": null}}\n", ua, ua);
+    let mut output = vec![];
+    let stats = LogEnricher::new(&client, LogFormat::JsonLines { ua_field: "ua".to_string() })
+        .capabilities(vec!["model_name"])
+        .unwrap()
+        .enrich(input.as_bytes(), &mut output)
+        .unwrap();
+    assert_eq!(EnrichStats { records: 2, enriched: 1, skipped: 1, errors: 0 }, stats);
+
+    // field order, number formatting and escapes are not changed; capabilities keep the column order
+    let output = String::from_utf8(output).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+    let expected = format!(
+        "{{\"z\": 1.50, \"ua\": {}, \"a\": \"\\u00e9\",\"wurfl\":{{\"wurfl_id\":\"nintendo_switch_ver1\",\"model_name\":\"Switch\"}}}}",
+        ua
+    );
+    assert_eq!(expected, lines[0]);
+    assert_eq!(format!("{{\"ua\": {}, \"wurfl\": null}}", ua), lines[1]);
+}
+
+#[test]
+fn enrich_csv_test() {
+    let (client, _) = create_mock_client();
+    let input = format!("time,user_agent\n1,\"{}\"\n2,{}\n", IPHONE_UA, BOT_UA.replace(',', ""));
+    let mut output = vec![];
+    let enricher = LogEnricher::new(&client, LogFormat::Csv { ua_column: "user_agent".to_string() }).capabilities(vec!["is_robot"]).unwrap();
+    assert_eq!(vec!["wurfl_id", "is_robot"], enricher.columns());
+    let stats = enricher.enrich(input.as_bytes(), &mut output).unwrap();
+    assert_eq!(2, stats.enriched);
+
+    let mut reader = csv::Reader::from_reader(output.as_slice());
+    assert_eq!(vec!["time", "user_agent", "wurfl_id", "is_robot"], reader.headers().unwrap().iter().collect::<Vec<&str>>());
+    let rows: Vec<csv::StringRecord> = reader.records().map(|r| r.unwrap()).collect();
+    assert_eq!(vec!["1", IPHONE_UA, "apple_iphone_ver10_2_1", "false"], rows[0].iter().collect::<Vec<&str>>());
+    assert_eq!(Some("true"), rows[1].get(3));
+
+    let err = LogEnricher::new(&client, LogFormat::Csv { ua_column: "agent".to_string() }).enrich(input.as_bytes(), vec![]).unwrap_err();
+    assert_eq!("CSV column agent not found", err.msg);
+}
+
+#[test]
+fn enrich_csv_invalid_utf8_test() {
+    let (client, _) = create_mock_client();
+    let mut input = b"time,user_agent\n1,Mozilla\xff\n2,\"".to_vec();
+    input.extend_from_slice(IPHONE_UA.as_bytes());
+    input.extend_from_slice(b"\"\n");
+    let mut output = vec![];
+    let stats = LogEnricher::new(&client, LogFormat::Csv { ua_column: "user_agent".to_string() })
+        .capabilities(vec!["brand_name"])
+        .unwrap()
+        .enrich(input.as_slice(), &mut output)
+        .unwrap();
+    assert_eq!(EnrichStats { records: 2, enriched: 1, skipped: 1, errors: 0 }, stats);
+
+    // the record is copied with empty capability columns, the next ones are still enriched
+    let mut reader = csv::Reader::from_reader(output.as_slice());
+    let rows: Vec<csv::ByteRecord> = reader.byte_records().map(|r| r.unwrap()).collect();
+    assert_eq!(vec![&b"1"[..], b"Mozilla\xff", b"", b""], rows[0].iter().collect::<Vec<&[u8]>>());
+    assert_eq!(Some(&b"Apple"[..]), rows[1].get(3));
+}
+
+#[test]
+fn enrich_columns_and_unreachable_server_test() {
+    let transport = MockTransport::new();
+    let down = transport.down.clone();
+    let mut client = WmClient::with_transport("http", "localhost", "8080", "", Box::new(transport)).unwrap();
+    // without requested capabilities all of them are added
+    assert_eq!(1 + client.static_caps.len() + client.virtual_caps.len(), LogEnricher::new(&client, LogFormat::Combined).columns().len());
+    client.set_requested_static_capabilities(Some(vec!["brand_name"]));
+    assert_eq!(vec!["wurfl_id", "brand_name"], LogEnricher::new(&client, LogFormat::Combined).columns());
+
+    // unknown names are rejected, rather than giving an empty set that would add all the capabilities
+    let err = LogEnricher::new(&client, LogFormat::Combined).capabilities(vec!["brand_nmae"]).err().unwrap();
+    assert_eq!(WmErrorKind::InvalidCapability, err.kind);
+
+    *down.lock().unwrap() = true;
+    let input = combined_line(SAMSUNG_UA);
+    let err = LogEnricher::new(&client, LogFormat::Combined).enrich(input.as_bytes(), vec![]).unwrap_err();
+    assert_eq!(WmErrorKind::Unreachable, err.kind);
+}
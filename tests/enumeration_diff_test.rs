@@ -0,0 +1,67 @@
+mod common;
+
+use common::*;
+use wmclient::*;
+
+fn mock_snapshot() -> EnumerationSnapshot {
+    let (client, _) = create_mock_client();
+    client.get_enumeration_snapshot().unwrap()
+}
+
+fn make_model(brand: &str, model: &str, marketing: Option<&str>) -> MakeModel {
+    MakeModel { brand_name: brand.to_string(), model_name: model.to_string(), marketing_name: marketing.map(|m| m.to_string()) }
+}
+
+// A newer snapshot: Nokia is gone, a Samsung model and a Google brand are added, the iPhone gets a marketing name
+// and the Galaxy S9+ loses its own, Android 12 and HarmonyOS appear, iOS 9.3 is gone
+fn updated_snapshot() -> EnumerationSnapshot {
+    let mut snapshot = mock_snapshot();
+    snapshot.info.wurfl_info = "/usr/share/wurfl/wurfl.zip:for WURFL API 1.13.0".to_string();
+    snapshot.make_models.retain(|m| m.brand_name != "Nokia");
+    snapshot.make_models.push(make_model("Samsung", "SM-S901B", Some("Galaxy S22")));
+    snapshot.make_models.push(make_model("Google", "Pixel 6", None));
+    for m in snapshot.make_models.iter_mut() {
+        if m.model_name == "iPhone" {
+            m.marketing_name = Some("iPhone 1st gen".to_string());
+        } else if m.model_name == "SM-G965F" {
+            m.marketing_name = None;
+        }
+    }
+    snapshot.os_versions.get_mut("Android").unwrap().push("12".to_string());
+    snapshot.os_versions.get_mut("iOS").unwrap().retain(|v| v != "9.3");
+    snapshot.os_versions.insert("HarmonyOS".to_string(), vec!["2.0".to_string()]);
+    snapshot
+}
+
+#[test]
+fn diff_between_snapshots_test() {
+    let diff = EnumerationDiff::between(&mock_snapshot(), &updated_snapshot());
+    assert!(!diff.is_empty());
+    assert_eq!("/usr/share/wurfl/wurfl.zip:for WURFL API 1.13.0", diff.new_wurfl_info);
+    assert_eq!(vec!["Google"], diff.brands.added);
+    assert_eq!(vec!["Nokia"], diff.brands.removed);
+    let model_names = |models: &Vec<BrandModel>| models.iter().map(|m| m.model_name.clone()).collect::<Vec<String>>();
+    assert_eq!(vec!["Pixel 6", "SM-S901B"], model_names(&diff.models.added));
+    assert_eq!(vec!["3310"], model_names(&diff.models.removed));
+    // only models found in both snapshots
+    assert_eq!(vec![make_model("Apple", "iPhone", Some("iPhone 1st gen"))], diff.marketing_names.added);
+    assert_eq!(vec![make_model("Samsung", "SM-G965F", Some("Galaxy S9+"))], diff.marketing_names.removed);
+    assert_eq!(vec!["HarmonyOS"], diff.oses.added);
+    assert!(diff.oses.removed.is_empty());
+    let version = |os: &str, v: &str| OsNameVersion { device_os: os.to_string(), device_os_version: v.to_string() };
+    assert_eq!(vec![version("Android", "12")], diff.os_versions.added);
+    assert_eq!(vec![version("iOS", "9.3")], diff.os_versions.removed);
+
+    let reverse = EnumerationDiff::between(&updated_snapshot(), &mock_snapshot());
+    assert_eq!(diff.brands.added, reverse.brands.removed);
+    assert!(EnumerationDiff::between(&mock_snapshot(), &mock_snapshot()).is_empty());
+}
+
+#[test]
+fn diff_with_live_server_test() {
+    let (client, _) = create_mock_client();
+    assert!(client.diff_enumerations(&mock_snapshot()).unwrap().is_empty());
+    let diff = client.diff_enumerations(&updated_snapshot()).unwrap();
+    assert_eq!(vec!["Nokia"], diff.brands.added);
+    assert_eq!(vec!["HarmonyOS"], diff.oses.removed);
+}
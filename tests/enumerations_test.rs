@@ -0,0 +1,124 @@
+mod common;
+
+use std::thread;
+use std::time::Duration;
+
+use common::*;
+use wmclient::*;
+
+fn create_client() -> (WmClient, MockCalls, std::sync::Arc<std::sync::Mutex<bool>>) {
+    let transport = MockTransport::new();
+    let calls = transport.calls.clone();
+    let down = transport.down.clone();
+    let client = WmClient::with_transport("http", "localhost", "8080", "", Box::new(transport)).unwrap();
+    (client, calls, down)
+}
+
+#[test]
+fn refresh_enumerations_test() {
+    let (client, calls, down) = create_client();
+    assert_eq!(4, client.get_all_device_makes().unwrap().len());
+    assert_eq!(3, client.get_all_oses().unwrap().len());
+    client.get_all_devices_for_make("Apple".to_string()).unwrap();
+    assert_eq!(1, calls.count("/v2/alldevices/json"));
+    assert_eq!(1, calls.count("/v2/alldeviceosversions/json"));
+
+    client.refresh_enumerations().unwrap();
+    assert_eq!(2, calls.count("/v2/alldevices/json"));
+    assert_eq!(2, calls.count("/v2/alldeviceosversions/json"));
+    client.get_all_oses().unwrap();
+    assert_eq!(2, calls.count("/v2/alldeviceosversions/json"));
+
+    // a failed refresh keeps the current data
+    *down.lock().unwrap() = true;
+    assert_eq!(WmErrorKind::Unreachable, client.refresh_enumerations().unwrap_err().kind);
+    assert_eq!(4, client.get_all_device_makes().unwrap().len());
+}
+
+#[test]
+fn enumerations_ttl_test() {
+    let (mut client, calls, down) = create_client();
+    client.set_enumerations_ttl(Some(Duration::from_millis(50)));
+    client.get_all_device_makes().unwrap();
+    client.get_all_device_makes().unwrap();
+    assert_eq!(1, calls.count("/v2/alldevices/json"));
+
+    thread::sleep(Duration::from_millis(60));
+    client.get_make_models().unwrap();
+    assert_eq!(2, calls.count("/v2/alldevices/json"));
+
+    // expired data is still used while the server is down
+    thread::sleep(Duration::from_millis(60));
+    *down.lock().unwrap() = true;
+    assert_eq!(4, client.get_all_device_makes().unwrap().len());
+    assert_eq!(3, calls.count("/v2/alldevices/json"));
+    // without data an unreachable server is an error
+    assert!(client.get_all_oses().is_err());
+}
+
+#[test]
+fn enumerations_single_download_test() {
+    let transport = MockTransport::new();
+    let calls = transport.calls.clone();
+    let mut client = WmClient::with_transport("http", "localhost", "8080", "", Box::new(transport.clone())).unwrap();
+    client.set_enumerations_ttl(Some(Duration::from_millis(50)));
+    *transport.delay.lock().unwrap() = 100;
+
+    // concurrent first uses wait for a single download
+    thread::scope(|scope| {
+        for _ in 0..8 {
+            scope.spawn(|| assert_eq!(4, client.get_all_device_makes().unwrap().len()));
+        }
+    });
+    assert_eq!(1, calls.count("/v2/alldevices/json"));
+
+    // once expired, one thread downloads the data again while the others get the expired data
+    thread::sleep(Duration::from_millis(60));
+    thread::scope(|scope| {
+        for _ in 0..8 {
+            scope.spawn(|| assert_eq!(4, client.get_all_device_makes().unwrap().len()));
+        }
+    });
+    assert_eq!(2, calls.count("/v2/alldevices/json"));
+}
+
+#[test]
+fn enumerations_without_ttl_never_expire_test() {
+    let (client, calls, _) = create_client();
+    client.get_all_versions_for_os("iOS").unwrap();
+    thread::sleep(Duration::from_millis(20));
+    client.get_all_versions_for_os("Android").unwrap();
+    assert_eq!(1, calls.count("/v2/alldeviceosversions/json"));
+    client.clear_caches();
+    client.get_all_oses().unwrap();
+    assert_eq!(2, calls.count("/v2/alldeviceosversions/json"));
+}
+
+#[test]
+fn concurrent_readers_see_whole_snapshots_test() {
+    let (client, _, _) = create_client();
+    client.get_all_device_makes().unwrap();
+    thread::scope(|scope| {
+        let writer = scope.spawn(|| {
+            for i in 0..200 {
+                if i % 2 == 0 {
+                    client.clear_caches();
+                } else {
+                    client.refresh_enumerations().unwrap();
+                }
+            }
+        });
+        for _ in 0..4 {
+            scope.spawn(|| {
+                for _ in 0..200 {
+                    // either the previous data or the new one, never a half cleared state
+                    assert_eq!(4, client.get_all_device_makes().unwrap().len());
+                    assert_eq!(4, client.get_all_devices_for_make("Samsung".to_string()).unwrap().len());
+                    assert_eq!(9, client.get_make_models().unwrap().len());
+                    assert_eq!(5, client.get_all_versions_for_os("Android").unwrap().len());
+                }
+            });
+        }
+        writer.join().unwrap();
+    });
+}
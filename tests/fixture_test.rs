@@ -0,0 +1,98 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use wmclient::*;
+
+const UA: &str = "Mozilla/5.0 (Nintendo Switch; WebApplet) AppleWebKit/601.6 (KHTML, like Gecko) NF/4.0.0.5.9 NintendoBrowser/5.1.0.13341";
+
+// Writes a fixture file holding the exchanges that a client needs to be created and to detect the UA above.
+fn write_test_fixture(name: &str) -> PathBuf {
+    let info = r#"{"wurfl_api_version":"1.12.0.0","wm_version":"2.1.0","wurfl_info":"/usr/share/wurfl/wurfl.zip:for WURFL API 1.12.0","important_headers":["User-Agent","X-UCBrowser-Device-UA","Device-Stock-UA"],"static_caps":["brand_name","model_name"],"virtual_caps":["form_factor","is_robot"],"ltime":"2021-09-01 10:00:00"}"#;
+    let device = r#"{"capabilities":{"wurfl_id":"nintendo_switch_ver1","brand_name":"Nintendo","model_name":"Switch","form_factor":"Other non-Mobile","is_robot":"false"},"error":"","mtime":1630490400,"ltime":"2021-09-01 10:00:00"}"#;
+
+    let exchanges = vec![
+        FixtureExchange { method: "GET".to_string(), path: "/v2/getinfo/json".to_string(), request: None, response: info.to_string() },
+        FixtureExchange {
+            method: "POST".to_string(),
+            path: "/v2/lookupuseragent/json".to_string(),
+            // key order differs from the one used by the client: the replayer must still match it
+            request: Some(serde_json::json!({"wurfl_id": null, "requested_vcaps": [], "requested_caps": [], "lookup_headers": {"User-Agent": UA}})),
+            response: device.to_string(),
+        },
+    ];
+    let mut content = String::new();
+    for exchange in exchanges {
+        content.push_str(serde_json::to_string(&exchange).unwrap().as_str());
+        content.push('\n');
+    }
+
+    let path = env::temp_dir().join(name);
+    fs::write(&path, content).unwrap();
+    path
+}
+
+#[test]
+fn replay_create_and_lookup_test() {
+    let path = write_test_fixture("wmclient_replay_lookup_test.jsonl");
+    let client_res = WmClient::new_replaying(path.to_str().unwrap());
+    assert!(client_res.is_ok());
+    let client = client_res.unwrap();
+    assert_eq!(3, client.important_headers.len());
+    assert!(client.has_static_capability("brand_name"));
+    assert!(client.has_virtual_capability("form_factor"));
+
+    let device_res = client.lookup_useragent(UA.to_string());
+    assert!(device_res.is_ok());
+    let device = device_res.unwrap();
+    assert_eq!("Nintendo", device.capabilities.get("brand_name").unwrap().as_str());
+    assert_eq!("Switch", device.capabilities.get("model_name").unwrap().as_str());
+    assert_eq!("", device.error);
+}
+
+#[test]
+fn replay_not_recorded_request_test() {
+    let path = write_test_fixture("wmclient_replay_missing_test.jsonl");
+    let client = WmClient::new_replaying(path.to_str().unwrap()).unwrap();
+    // this user-agent has never been recorded, no network access is attempted
+    let device_res = client.lookup_useragent("Opera/9.80 (Android; Opera Mini/51.0.2254/184.121; U; en)".to_string());
+    assert!(device_res.is_err());
+    assert!(device_res.err().unwrap().msg.contains("/v2/lookupuseragent/json"));
+}
+
+#[test]
+fn replay_with_cache_test() {
+    let path = write_test_fixture("wmclient_replay_cache_test.jsonl");
+    let mut client = WmClient::new_replaying(path.to_str().unwrap()).unwrap();
+    client.set_cache_size(100);
+    for _i in 0..10 {
+        let device_res = client.lookup_useragent(UA.to_string());
+        assert!(device_res.is_ok());
+    }
+    assert_eq!(1, client.get_actual_cache_sizes().1);
+}
+
+#[test]
+fn replay_missing_fixture_file_test() {
+    let path = env::temp_dir().join("wmclient_does_not_exist.jsonl");
+    let client_res = WmClient::new_replaying(path.to_str().unwrap());
+    assert!(client_res.is_err());
+}
+
+#[test]
+fn replay_invalid_fixture_file_test() {
+    let path = env::temp_dir().join("wmclient_replay_invalid_test.jsonl");
+    fs::write(&path, "this is not json\n").unwrap();
+    let client_res = WmClient::new_replaying(path.to_str().unwrap());
+    assert!(client_res.is_err());
+    assert!(client_res.err().unwrap().msg.contains("line 1"));
+}
+
+#[test]
+fn record_with_server_down_test() {
+    let path = env::temp_dir().join("wmclient_record_server_down_test.jsonl");
+    let client_res = WmClient::new_recording("http", "localhost", "18080", "", path.to_str().unwrap());
+    assert!(client_res.is_err());
+    // nothing has been exchanged with the server, so the fixture is empty
+    assert_eq!("", fs::read_to_string(&path).unwrap());
+}
@@ -0,0 +1,180 @@
+mod common;
+
+use common::*;
+use wmclient::*;
+
+fn strict_policy() -> HeaderPolicy {
+    HeaderPolicy { decoding: HeaderDecoding::Strict, ..HeaderPolicy::default() }
+}
+
+fn rejecting_policy() -> HeaderPolicy {
+    HeaderPolicy { over_limit: HeaderLimitAction::Reject, ..HeaderPolicy::default() }
+}
+
+fn invalid_utf8_ua() -> Vec<u8> {
+    let mut ua = SAMSUNG_UA.as_bytes().to_vec();
+    ua.extend_from_slice(&[0xff, 0xfe, 0xc3]);
+    ua
+}
+
+#[test]
+fn default_header_policy_test() {
+    let policy = HeaderPolicy::default();
+    assert_eq!(HeaderDecoding::Lossy, policy.decoding);
+    assert_eq!(8192, policy.max_value_length);
+    assert_eq!(100, policy.max_header_count);
+    assert_eq!(HeaderLimitAction::Ignore, policy.over_limit);
+}
+
+#[test]
+fn lossy_invalid_utf8_test() {
+    let (client, calls) = create_mock_client();
+    let headers: Vec<(&str, Vec<u8>)> = vec![("User-Agent", invalid_utf8_ua())];
+    let device_res = client.lookup_headers(headers);
+    assert!(device_res.is_ok());
+    assert_eq!("SM-G960F", device_res.unwrap().capabilities.get("model_name").unwrap().as_str());
+    let sent_ua = calls.last_post()["lookup_headers"]["User-Agent"].as_str().unwrap().to_string();
+    assert!(sent_ua.starts_with(SAMSUNG_UA));
+    assert!(sent_ua.ends_with('\u{FFFD}'));
+}
+
+#[test]
+fn skip_invalid_utf8_test() {
+    let (mut client, calls) = create_mock_client();
+    client.set_header_policy(HeaderPolicy { decoding: HeaderDecoding::Skip, ..HeaderPolicy::default() });
+    let headers: Vec<(&str, Vec<u8>)> = vec![
+        ("User-Agent", invalid_utf8_ua()),
+        ("Device-Stock-UA", IPHONE_UA.as_bytes().to_vec()),
+    ];
+    let device = client.lookup_headers(headers).unwrap();
+    assert_eq!("apple_iphone_ver10_2_1", device.capabilities.get("wurfl_id").unwrap().as_str());
+    let sent_headers = calls.last_post()["lookup_headers"].as_object().unwrap().clone();
+    assert_eq!(1, sent_headers.len());
+    assert!(sent_headers.get("User-Agent").is_none());
+}
+
+#[test]
+fn strict_invalid_utf8_test() {
+    let (mut client, calls) = create_mock_client();
+    client.set_header_policy(strict_policy());
+    let headers: Vec<(&str, Vec<u8>)> = vec![("User-Agent", invalid_utf8_ua())];
+    let device_res = client.lookup_headers(headers);
+    assert!(device_res.is_err());
+    let err = device_res.err().unwrap();
+    assert_eq!(WmErrorKind::InvalidHeader, err.kind);
+    assert!(err.msg.contains("User-Agent"));
+    // no request is sent to the server
+    assert!(calls.posts().is_empty());
+}
+
+#[test]
+fn invalid_utf8_in_ignored_header_test() {
+    // headers that are not sent to the server are never decoded, not even in strict mode
+    let (mut client, _) = create_mock_client();
+    client.set_header_policy(strict_policy());
+    let headers: Vec<(&str, Vec<u8>)> = vec![
+        ("Cookie", vec![0xff, 0x00, 0xfe]),
+        ("User-Agent", SAMSUNG_UA.as_bytes().to_vec()),
+    ];
+    assert!(client.lookup_headers(headers).is_ok());
+}
+
+#[test]
+fn oversized_header_value_test() {
+    let (mut client, calls) = create_mock_client();
+    client.set_header_policy(HeaderPolicy { max_value_length: 64, ..HeaderPolicy::default() });
+    let long_ua = format!("{}{}", SAMSUNG_UA, "x".repeat(100));
+    let headers = vec![("User-Agent", long_ua.clone()), ("Device-Stock-UA", "short".to_string())];
+    assert!(client.lookup_headers(headers).is_ok());
+    let sent_headers = calls.last_post()["lookup_headers"].as_object().unwrap().clone();
+    assert!(sent_headers.get("User-Agent").is_none());
+    assert_eq!("short", sent_headers["Device-Stock-UA"].as_str().unwrap());
+
+    // strict decoding alone does not reject oversized values
+    client.set_header_policy(HeaderPolicy { max_value_length: 64, ..strict_policy() });
+    assert!(client.lookup_headers(vec![("User-Agent", long_ua.clone())]).is_ok());
+
+    client.set_header_policy(HeaderPolicy { max_value_length: 64, ..rejecting_policy() });
+    let device_res = client.lookup_headers(vec![("User-Agent", long_ua)]);
+    assert!(device_res.is_err());
+    assert_eq!(WmErrorKind::InvalidHeader, device_res.err().unwrap().kind);
+}
+
+#[test]
+fn max_header_count_test() {
+    let (mut client, calls) = create_mock_client();
+    client.set_header_policy(HeaderPolicy { max_header_count: 1, ..HeaderPolicy::default() });
+    let headers = vec![
+        ("Accept", "*/*"),
+        ("Accept-Language", "en"),
+        ("Device-Stock-UA", IPHONE_UA),
+        ("User-Agent", SAMSUNG_UA),
+    ];
+    // only the headers sent to the server are counted: the ones after the first are ignored
+    let device = client.lookup_headers(headers.clone()).unwrap();
+    assert_eq!("apple_iphone_ver10_2_1", device.capabilities.get("wurfl_id").unwrap().as_str());
+    assert!(calls.last_post()["lookup_headers"].get("User-Agent").is_none());
+
+    client.set_header_policy(HeaderPolicy { max_header_count: 1, ..rejecting_policy() });
+    let device_res = client.lookup_headers(headers);
+    assert!(device_res.is_err());
+    assert_eq!(WmErrorKind::InvalidHeader, device_res.err().unwrap().kind);
+}
+
+#[test]
+fn max_header_count_other_headers_test() {
+    // headers that are not sent to the server do not count, even in large numbers
+    let (mut client, _) = create_mock_client();
+    client.set_header_policy(HeaderPolicy { max_header_count: 2, ..rejecting_policy() });
+    let mut headers: Vec<(String, String)> = (0..150).map(|i| (format!("X-Header-{}", i), "v".to_string())).collect();
+    headers.push(("User-Agent".to_string(), SAMSUNG_UA.to_string()));
+    let device = client.lookup_headers(headers).unwrap();
+    assert_eq!("samsung_sm_g960f_ver1", device.capabilities.get("wurfl_id").unwrap().as_str());
+}
+
+// xorshift pseudo random generator, so that the test is reproducible
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn bytes(&mut self, max_len: usize) -> Vec<u8> {
+        let len = (self.next() as usize) % max_len;
+        (0..len).map(|_| self.next() as u8).collect()
+    }
+}
+
+#[test]
+fn arbitrary_header_bytes_test() {
+    let (mut client, _) = create_mock_client();
+    let mut rng = Rng(0x2545F4914F6CDD1D);
+    let names = ["User-Agent", "user-agent", "Device-Stock-UA", "Sec-CH-UA", "Sec-CH-UA-Model", "X-Random"];
+    let policies = [
+        HeaderPolicy::default(),
+        HeaderPolicy { decoding: HeaderDecoding::Skip, ..HeaderPolicy::default() },
+        HeaderPolicy { max_value_length: 16, max_header_count: 4, over_limit: HeaderLimitAction::Reject, ..strict_policy() },
+    ];
+    for i in 0..600 {
+        let policy = policies[i % policies.len()];
+        client.set_header_policy(policy);
+        let mut headers: Vec<(String, Vec<u8>)> = vec![];
+        for _h in 0..(rng.next() % 8) {
+            let name = names[(rng.next() as usize) % names.len()].to_string();
+            headers.push((name, rng.bytes(64)));
+        }
+        // whatever the bytes are, the lookup never panics: it either succeeds or returns an InvalidHeader error
+        match client.lookup_headers(headers) {
+            Ok(device) => assert!(device.capabilities.contains_key("wurfl_id")),
+            Err(err) => {
+                assert_eq!(HeaderDecoding::Strict, policy.decoding);
+                assert_eq!(HeaderLimitAction::Reject, policy.over_limit);
+                assert_eq!(WmErrorKind::InvalidHeader, err.kind);
+            }
+        }
+    }
+}
@@ -0,0 +1,103 @@
+#![cfg(feature = "http")]
+mod common;
+
+use http::header::{HeaderMap, HeaderName, HeaderValue};
+use common::*;
+use wmclient::*;
+
+#[test]
+fn lookup_request_test() {
+    let (client, calls) = create_mock_client();
+    let req = http::Request::builder()
+        .header("user-agent", SAMSUNG_UA)
+        .header("DEVICE-STOCK-UA", "stock")
+        .header("Accept", "*/*")
+        .body(())
+        .unwrap();
+    let device = client.lookup_request(&req).unwrap();
+    assert_eq!("samsung_sm_g960f_ver1", device.capabilities.get("wurfl_id").unwrap().as_str());
+    // only the lookup headers are sent, with their canonical names
+    let sent_headers = calls.last_post()["lookup_headers"].as_object().unwrap().clone();
+    assert_eq!(2, sent_headers.len());
+    assert_eq!(SAMSUNG_UA, sent_headers["User-Agent"].as_str().unwrap());
+    assert_eq!("stock", sent_headers["Device-Stock-UA"].as_str().unwrap());
+}
+
+#[test]
+fn lookup_header_map_repeated_headers_test() {
+    let (client, calls) = create_mock_client();
+    let mut headers = HeaderMap::new();
+    headers.insert("User-Agent", HeaderValue::from_static(IPHONE_UA));
+    headers.append("Sec-CH-UA", HeaderValue::from_static("\"Chromium\";v=\"110\""));
+    headers.append("sec-ch-ua", HeaderValue::from_static("\"Google Chrome\";v=\"110\""));
+    // empty values are not folded
+    headers.append("Sec-CH-UA", HeaderValue::from_static(""));
+    let device = client.lookup_header_map(&headers).unwrap();
+    assert_eq!("apple_iphone_ver10_2_1", device.capabilities.get("wurfl_id").unwrap().as_str());
+    let sent_headers = calls.last_post()["lookup_headers"].as_object().unwrap().clone();
+    assert_eq!("\"Chromium\";v=\"110\", \"Google Chrome\";v=\"110\"", sent_headers["Sec-CH-UA"].as_str().unwrap());
+}
+
+#[test]
+fn lookup_header_map_same_as_lookup_headers_test() {
+    let (client, calls) = create_mock_client();
+    let mut headers = HeaderMap::new();
+    headers.insert("User-Agent", HeaderValue::from_static(SWITCH_UA));
+    headers.insert("X-OperaMini-Phone-UA", HeaderValue::from_static("opera"));
+    headers.insert("Cookie", HeaderValue::from_static("a=b"));
+    let d1 = client.lookup_header_map(&headers).unwrap();
+    let sent_by_map = calls.last_post()["lookup_headers"].clone();
+    let d2 = client.lookup_headers(&headers).unwrap();
+    assert_eq!(sent_by_map, calls.last_post()["lookup_headers"]);
+    assert_eq!(d1.capabilities, d2.capabilities);
+}
+
+#[test]
+fn lookup_header_map_invalid_values_test() {
+    let (mut client, calls) = create_mock_client();
+    let mut headers = HeaderMap::new();
+    headers.insert("User-Agent", HeaderValue::from_bytes(&[b'a', 0xff, 0xfe]).unwrap());
+    headers.insert("Device-Stock-UA", HeaderValue::from_static(SAMSUNG_UA));
+    assert!(client.lookup_header_map(&headers).is_ok());
+    assert!(calls.last_post()["lookup_headers"]["User-Agent"].as_str().unwrap().ends_with('\u{FFFD}'));
+
+    client.set_header_policy(HeaderPolicy { decoding: HeaderDecoding::Strict, ..HeaderPolicy::default() });
+    let device_res = client.lookup_header_map(&headers);
+    assert_eq!(WmErrorKind::InvalidHeader, device_res.err().unwrap().kind);
+
+    // headers not sent to the server are not counted, too many of the others are an error if the policy rejects them
+    client.set_header_policy(HeaderPolicy { max_header_count: 2, over_limit: HeaderLimitAction::Reject, ..HeaderPolicy::default() });
+    let mut headers = HeaderMap::new();
+    for i in 0..3 {
+        headers.insert(HeaderName::from_bytes(format!("X-Header-{}", i).as_bytes()).unwrap(), HeaderValue::from_static("v"));
+    }
+    headers.insert("User-Agent", HeaderValue::from_static(SAMSUNG_UA));
+    assert!(client.lookup_header_map(&headers).is_ok());
+    headers.append("User-Agent", HeaderValue::from_static(SAMSUNG_UA));
+    headers.insert("Device-Stock-UA", HeaderValue::from_static(SAMSUNG_UA));
+    assert_eq!(WmErrorKind::InvalidHeader, client.lookup_header_map(&headers).err().unwrap().kind);
+}
+
+#[test]
+fn lookup_header_map_cache_test() {
+    let (mut client, calls) = create_mock_client();
+    client.set_cache_size(100);
+    let mut headers = HeaderMap::new();
+    headers.insert("User-Agent", HeaderValue::from_static(SAMSUNG_UA));
+    client.lookup_header_map(&headers).unwrap();
+    // the map and the iterator based lookups share the same cache entries
+    let device = client.lookup_headers(vec![("User-Agent", SAMSUNG_UA)]).unwrap();
+    assert_eq!("samsung_sm_g960f_ver1", device.capabilities.get("wurfl_id").unwrap().as_str());
+    assert_eq!(1, calls.posts().len());
+}
+
+#[test]
+fn lookup_request_with_caps_test() {
+    let (client, calls) = create_mock_client();
+    let caps = CapabilitySet::new(&client, vec!["device_os", "is_ios"]);
+    let req = http::Request::builder().header("User-Agent", IPHONE_UA).body(()).unwrap();
+    let device = client.lookup_request_with(&caps, &req).unwrap();
+    assert_eq!(3, device.capabilities.len());
+    assert_eq!("iOS", device.capabilities.get("device_os").unwrap().as_str());
+    assert_eq!("is_ios", calls.last_post()["requested_vcaps"][0].as_str().unwrap());
+}
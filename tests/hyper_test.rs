@@ -0,0 +1,56 @@
+#![cfg(feature = "hyper")]
+mod common;
+
+use std::convert::Infallible;
+
+use common::*;
+use http_body_util::{BodyExt, Empty, Full};
+use hyper::body::Bytes;
+use hyper::service::{service_fn, Service};
+use wmclient::*;
+
+#[test]
+fn lookup_request_test() {
+    let (client, calls) = create_mock_client();
+    let req = http::Request::builder()
+        .header("user-agent", SAMSUNG_UA)
+        .header("Sec-CH-UA-Model", "\"SM-G960F\"")
+        .header("Accept", "*/*")
+        .body(())
+        .unwrap();
+    let device = client.lookup_request(&req).unwrap();
+    assert_eq!("samsung_sm_g960f_ver1", device.capabilities.get("wurfl_id").unwrap().as_str());
+    let sent_headers = calls.last_post()["lookup_headers"].as_object().unwrap().clone();
+    assert_eq!(2, sent_headers.len());
+    assert_eq!(SAMSUNG_UA, sent_headers["User-Agent"].as_str().unwrap());
+}
+
+// Inner service that answers with the WURFL ID of the detected device, if any
+async fn echo_device(req: http::Request<Empty<Bytes>>) -> Result<http::Response<Full<Bytes>>, Infallible> {
+    let wurfl_id = match req.extensions().get::<DetectedDevice>() {
+        Some(detected) => detected.device.capabilities.get("wurfl_id").unwrap().to_string(),
+        None => "none".to_string(),
+    };
+    Ok(http::Response::new(Full::new(Bytes::from(wurfl_id))))
+}
+
+async fn body_text(response: http::Response<Full<Bytes>>) -> String {
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    String::from_utf8(bytes.to_vec()).unwrap()
+}
+
+#[tokio::test]
+async fn hyper_service_test() {
+    let transport = MockTransport::new();
+    let down = transport.down.clone();
+    let client = WmClient::with_transport("http", "localhost", "8080", "", Box::new(transport)).unwrap();
+    let service = WmHyperService::new(AsyncWmClient::new(client), service_fn(echo_device));
+
+    let req = http::Request::builder().header("User-Agent", IPHONE_UA).body(Empty::new()).unwrap();
+    assert_eq!("apple_iphone_ver10_2_1", body_text(service.call(req).await.unwrap()).await);
+
+    // detection errors do not stop the request
+    *down.lock().unwrap() = true;
+    let req = http::Request::builder().header("User-Agent", BOT_UA).body(Empty::new()).unwrap();
+    assert_eq!("none", body_text(service.call(req).await.unwrap()).await);
+}
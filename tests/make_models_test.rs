@@ -0,0 +1,73 @@
+mod common;
+
+use common::*;
+use wmclient::*;
+
+// Serves a device list with a duplicated record, on top of the mock server
+struct DuplicatesTransport {
+    inner: MockTransport,
+}
+
+impl Transport for DuplicatesTransport {
+    fn get(&self, url: &str) -> Result<String, WmError> {
+        if url.ends_with("/v2/alldevices/json") {
+            return Ok(r#"[
+                {"brand_name":"Samsung","model_name":"SM-G960F","marketing_name":"Galaxy S9"},
+                {"brand_name":"Samsung","model_name":"SM-G960F","marketing_name":"Galaxy S9"},
+                {"brand_name":"Apple","model_name":"iPhone","marketing_name":null},
+                {"brand_name":"Generic","model_name":"iPhone","marketing_name":""}
+            ]"#.to_string());
+        }
+        self.inner.get(url)
+    }
+
+    fn post(&self, url: &str, body: &str) -> Result<String, WmError> {
+        self.inner.post(url, body)
+    }
+}
+
+#[test]
+fn make_models_records_test() {
+    let (client, calls) = create_mock_client();
+    let make_models = client.get_make_models().unwrap();
+    assert_eq!(9, make_models.len());
+    // records come in the server order, missing marketing names stay missing
+    let first = make_models.iter().next().unwrap();
+    assert_eq!(MakeModel { brand_name: "Samsung".to_string(), model_name: "SM-G960F".to_string(), marketing_name: Some("Galaxy S9".to_string()) }, *first);
+    let iphone = make_models.iter().find(|m| m.model_name == "iPhone").unwrap();
+    assert_eq!(None, iphone.marketing_name);
+    assert_eq!(3, (&make_models).into_iter().filter(|m| m.marketing_name.is_none()).count());
+
+    // all the enumerations share the same download
+    client.get_all_device_makes().unwrap();
+    client.get_all_devices_for_make("Apple".to_string()).unwrap();
+    client.get_device_catalog().unwrap();
+    assert_eq!(1, calls.count("/v2/alldevices/json"));
+}
+
+#[test]
+fn make_models_grouping_test() {
+    let (client, _) = create_mock_client();
+    let make_models = client.get_make_models().unwrap();
+    let by_brand = make_models.by_brand();
+    assert_eq!(vec!["Apple", "Nintendo", "Nokia", "Samsung"], by_brand.keys().copied().collect::<Vec<&str>>());
+    assert_eq!(4, by_brand["Samsung"].len());
+    let by_model = make_models.by_model();
+    assert_eq!("Nintendo", by_model["Switch"][0].brand_name);
+}
+
+#[test]
+fn make_models_keep_duplicates_test() {
+    let transport = DuplicatesTransport { inner: MockTransport::new() };
+    let client = WmClient::with_transport("http", "localhost", "8080", "", Box::new(transport)).unwrap();
+    let make_models = client.get_make_models().unwrap();
+    assert_eq!(4, make_models.len());
+    assert_eq!(2, make_models.by_brand()["Samsung"].len());
+    let iphones = &make_models.by_model()["iPhone"];
+    assert_eq!(vec![None, Some("".to_string())], iphones.iter().map(|m| m.marketing_name.clone()).collect::<Vec<Option<String>>>());
+    // the catalog is an index: duplicates are merged
+    assert_eq!(3, client.get_device_catalog().unwrap().len());
+
+    client.clear_caches();
+    assert_eq!(4, client.get_make_models().unwrap().len());
+}
@@ -0,0 +1,41 @@
+mod common;
+
+use common::*;
+
+#[test]
+fn enumerations_ignore_case_and_punctuation_test() {
+    let (client, _) = create_mock_client();
+    let models = client.get_all_devices_for_make("apple".to_string()).unwrap();
+    assert_eq!(3, models.len());
+    assert_eq!(client.get_all_versions_for_os("Android").unwrap(), client.get_all_versions_for_os(" android ").unwrap());
+    assert_eq!(vec!["1.0"], client.get_all_versions_for_os("nokia-os").unwrap());
+
+    // exact names still work, unknown names still fail
+    assert_eq!(4, client.get_all_devices_for_make("Samsung".to_string()).unwrap().len());
+    let err = client.get_all_devices_for_make("NotExisting".to_string()).unwrap_err();
+    assert!(err.msg.contains("NotExisting"));
+    let err = client.get_all_versions_for_os("Apple").unwrap_err();
+    assert!(err.msg.contains("does not exist"));
+}
+
+#[test]
+fn enumerations_canonical_names_test() {
+    let (client, _) = create_mock_client();
+    let found = client.find_versions_for_os("iPhone OS").unwrap();
+    assert_eq!("iOS", found.canonical_name);
+    assert_eq!(vec!["9.3", "10.2.1", "14.0"], found.values);
+    assert_eq!("iOS", client.resolve_os_name("IOS").unwrap());
+
+    let found = client.find_devices_for_make("SAMSUNG ELECTRONICS").unwrap();
+    assert_eq!("Samsung", found.canonical_name);
+    assert_eq!(4, found.values.len());
+    assert_eq!("Nokia", client.resolve_make_name("nokia.").unwrap());
+
+    let err = client.find_devices_for_make("Motorola").unwrap_err();
+    assert!(err.msg.contains("Motorola"));
+    assert!(client.resolve_os_name("").is_err());
+
+    let catalog = client.get_device_catalog().unwrap();
+    assert_eq!(Some("Apple"), catalog.find_brand("Apple Inc."));
+    assert_eq!(3, catalog.devices_for_brand("apple inc").len());
+}
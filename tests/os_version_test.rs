@@ -0,0 +1,54 @@
+mod common;
+
+use std::cmp::Ordering;
+
+use common::*;
+use wmclient::*;
+
+fn sorted(versions: Vec<&str>) -> Vec<String> {
+    let mut parsed: Vec<OsVersion> = versions.into_iter().map(OsVersion::parse).collect();
+    parsed.sort();
+    parsed.iter().map(|v| v.to_string()).collect()
+}
+
+#[test]
+fn os_version_ordering_test() {
+    assert_eq!(vec!["4.4", "8.0", "9", "10", "10.0.1", "11"], sorted(vec!["10", "11", "4.4", "9", "8.0", "10.0.1"]));
+    assert_eq!(vec!["9.3", "10_2", "10.2.1", "14.0"], sorted(vec!["14.0", "10.2.1", "9.3", "10_2"]));
+    // words after the same numbers are pre-releases
+    assert_eq!(vec!["11.0 Beta", "11.0", "11.0.1"], sorted(vec!["11.0.1", "11.0", "11.0 Beta"]));
+    assert_eq!(vec!["XP", "7", "8.1"], sorted(vec!["8.1", "XP", "7"]));
+
+    // trailing zeros only matter to keep the order total
+    assert_eq!(Ordering::Equal, OsVersion::parse("8").cmp_version(&OsVersion::parse("8.0")));
+    assert_eq!(Ordering::Less, OsVersion::parse("8").cmp(&OsVersion::parse("8.0")));
+    assert_eq!(OsVersion::parse(" 10 "), OsVersion::parse("10"));
+}
+
+#[test]
+fn os_version_parts_test() {
+    let version = OsVersion::parse("10.2.1");
+    assert_eq!(vec![10, 2, 1], version.numbers());
+    assert_eq!(Some(10), version.major());
+    assert_eq!("10.2.1", version.as_str());
+    assert_eq!(vec![11, 0], OsVersion::parse("11.0 Beta 2").numbers());
+    assert_eq!(vec![5, 1], OsVersion::parse("5.1Lollipop").numbers());
+    assert_eq!(None, OsVersion::parse("Vista").major());
+    assert_eq!(None, OsVersion::parse("").major());
+    assert_eq!(OsVersion::parse("7.1"), "7.1".parse::<OsVersion>().unwrap());
+}
+
+#[test]
+fn os_versions_from_client_test() {
+    let (client, _) = create_mock_client();
+    assert_eq!(vec!["4.4", "8.0", "9", "10", "11"], client.get_all_versions_for_os("Android").unwrap());
+    let versions = client.get_os_versions("android").unwrap();
+    assert_eq!(vec!["4.4", "8.0", "9", "10", "11"], versions.iter().map(|v| v.as_str()).collect::<Vec<&str>>());
+
+    let between = client.versions_between("Android", "8", "10.0").unwrap();
+    assert_eq!(vec!["8.0", "9", "10"], between.iter().map(|v| v.as_str()).collect::<Vec<&str>>());
+    assert!(client.versions_between("Android", "12", "13").unwrap().is_empty());
+
+    assert_eq!(Some(OsVersion::parse("14.0")), client.latest_version("iOS").unwrap());
+    assert!(client.latest_version("Palm OS").is_err());
+}
@@ -0,0 +1,99 @@
+mod common;
+
+use common::*;
+use wmclient::*;
+
+fn ads_profile() -> CapabilityProfile {
+    CapabilityProfile::new("ads", vec!["is_smartphone", "form_factor", "resolution_width"])
+}
+
+fn analytics_profile() -> CapabilityProfile {
+    CapabilityProfile::new("analytics", vec!["brand_name", "model_name", "device_os", "device_os_version"])
+}
+
+fn sorted_names(device: &JSONDeviceData) -> Vec<&str> {
+    let mut names: Vec<&str> = device.capabilities.keys().map(|k| k.as_str()).collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn register_profile_test() {
+    let (mut client, _) = create_mock_client();
+    assert!(client.register_profile(ads_profile()).is_ok());
+    assert!(client.register_profile(analytics_profile()).is_ok());
+    assert_eq!(vec!["ads", "analytics"], client.get_profile_names());
+
+    let ads = client.profile("ads").unwrap();
+    assert_eq!("ads", ads.name());
+    assert_eq!(vec!["resolution_width"], ads.capabilities().static_caps());
+    assert_eq!(vec!["form_factor", "is_smartphone"], ads.capabilities().virtual_caps());
+
+    let err = client.profile("marketing").err().unwrap();
+    assert_eq!(WmErrorKind::InvalidCapability, err.kind);
+}
+
+#[test]
+fn register_invalid_profile_test() {
+    let (mut client, _) = create_mock_client();
+    let err = client.register_profile(CapabilityProfile::new("bad", vec!["brand_name", "brand_nam", "is_phone"])).err().unwrap();
+    assert_eq!(WmErrorKind::InvalidCapability, err.kind);
+    assert!(err.msg.starts_with("Invalid profile bad: "));
+    assert!(err.msg.contains("brand_nam (did you mean brand_name?)"));
+    assert!(client.get_profile_names().is_empty());
+
+    client.register_profile(ads_profile()).unwrap();
+    let err = client.register_profile(ads_profile()).err().unwrap();
+    assert!(err.msg.contains("already registered"));
+}
+
+#[test]
+fn profile_lookups_test() {
+    let (mut client, calls) = create_mock_client();
+    client.register_profile(ads_profile()).unwrap();
+    client.register_profile(analytics_profile()).unwrap();
+
+    let device = client.profile("ads").unwrap().lookup_headers(vec![("User-Agent", SAMSUNG_UA)]).unwrap();
+    assert_eq!(vec!["form_factor", "is_smartphone", "resolution_width", "wurfl_id"], sorted_names(&device));
+    let device = client.profile("analytics").unwrap().lookup_useragent(IPHONE_UA.to_string()).unwrap();
+    assert_eq!(vec!["brand_name", "device_os", "device_os_version", "model_name", "wurfl_id"], sorted_names(&device));
+    assert_eq!("analytics", client.profile("analytics").unwrap().name());
+    let device = client.profile("ads").unwrap().lookup_device_id("google_bot".to_string()).unwrap();
+    assert_eq!(4, device.capabilities.len());
+    assert_eq!(3, calls.posts().len());
+
+    // the client requested capabilities are not changed
+    let device = client.lookup_useragent(IPHONE_UA.to_string()).unwrap();
+    assert!(device.capabilities.len() > 5);
+}
+
+#[test]
+fn profile_cache_and_stats_test() {
+    let (mut client, calls) = create_mock_client();
+    client.register_profile(ads_profile()).unwrap();
+    // profiles registered before setting the cache size get a cache too
+    client.set_cache_size(100);
+    client.register_profile(analytics_profile()).unwrap();
+
+    let ads = client.profile("ads").unwrap();
+    let analytics = client.profile("analytics").unwrap();
+    for _ in 0..3 {
+        ads.lookup_useragent(SAMSUNG_UA.to_string()).unwrap();
+        analytics.lookup_useragent(SAMSUNG_UA.to_string()).unwrap();
+    }
+    analytics.lookup_useragent(SWITCH_UA.to_string()).unwrap();
+    // one request per profile and user agent, the client cache is not used
+    assert_eq!(3, calls.posts().len());
+    assert_eq!((0, 0), client.get_actual_cache_sizes());
+
+    assert_eq!(ProfileStats { lookups: 3, cache_hits: 2, errors: 0 }, ads.stats());
+    assert_eq!(ProfileStats { lookups: 4, cache_hits: 2, errors: 0 }, analytics.stats());
+
+    assert!(ads.lookup_device_id("not_a_device".to_string()).is_err());
+    assert_eq!(ProfileStats { lookups: 4, cache_hits: 2, errors: 1 }, client.profile("ads").unwrap().stats());
+
+    // clearing the client caches clears the profile ones
+    client.clear_caches();
+    ads.lookup_useragent(SAMSUNG_UA.to_string()).unwrap();
+    assert_eq!(5, calls.posts().len());
+}
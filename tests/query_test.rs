@@ -0,0 +1,126 @@
+mod common;
+
+use common::*;
+use wmclient::*;
+
+fn all_device_ids() -> Vec<String> {
+    ["samsung_sm_g960f_ver1", "apple_iphone_ver10_2_1", "nintendo_switch_ver1", "google_bot", "generic"]
+        .iter().map(|id| id.to_string()).collect()
+}
+
+fn wurfl_ids(devices: &[JSONDeviceData]) -> Vec<String> {
+    let mut ids: Vec<String> = devices.iter().map(|d| d.capabilities["wurfl_id"].clone()).collect();
+    ids.sort();
+    ids
+}
+
+#[test]
+fn query_predicates_test() {
+    let (client, calls) = create_mock_client();
+    let devices = client.query()
+        .filter(CapabilityPredicate::at_least("resolution_width", "1200"))
+        .collect(all_device_ids())
+        .unwrap();
+    assert_eq!(vec!["nintendo_switch_ver1", "samsung_sm_g960f_ver1"], wurfl_ids(&devices));
+    // only the capabilities the query is about are requested
+    assert_eq!(vec!["resolution_width", "wurfl_id"], {
+        let mut names: Vec<&String> = devices[0].capabilities.keys().collect();
+        names.sort();
+        names
+    });
+    assert_eq!(5, calls.count("/v2/lookupdeviceid/json"));
+
+    let devices = client.query()
+        .filter(CapabilityPredicate::equals("is_smartphone", "true"))
+        .filter(CapabilityPredicate::less_than("resolution_width", "1000"))
+        .capabilities(vec!["model_name"])
+        .collect(all_device_ids())
+        .unwrap();
+    assert_eq!(vec!["apple_iphone_ver10_2_1"], wurfl_ids(&devices));
+    assert_eq!("iPhone", devices[0].capabilities["model_name"]);
+
+    // versions are compared by their numbers
+    let devices = client.query()
+        .filter(CapabilityPredicate::at_least("device_os_version", "9.3"))
+        .collect(all_device_ids())
+        .unwrap();
+    assert_eq!(vec!["apple_iphone_ver10_2_1", "samsung_sm_g960f_ver1"], wurfl_ids(&devices));
+    let devices = client.query()
+        .filter(CapabilityPredicate::not_equals("form_factor", "Smartphone"))
+        .collect(all_device_ids())
+        .unwrap();
+    assert_eq!(vec!["generic", "google_bot", "nintendo_switch_ver1"], wurfl_ids(&devices));
+}
+
+#[test]
+fn query_brand_and_os_test() {
+    let (client, calls) = create_mock_client();
+    let devices = client.query().brand("samsung electronics").collect(all_device_ids()).unwrap();
+    assert_eq!(vec!["samsung_sm_g960f_ver1"], wurfl_ids(&devices));
+    assert_eq!("Samsung", devices[0].capabilities["brand_name"]);
+    let devices = client.query().os("iPhone OS").collect(all_device_ids()).unwrap();
+    assert_eq!(vec!["apple_iphone_ver10_2_1"], wurfl_ids(&devices));
+    assert!(client.query().brand("Apple").os("Android").collect(all_device_ids()).unwrap().is_empty());
+
+    // a brand the server does not list: no lookups
+    let lookups = calls.count("/v2/lookupdeviceid/json");
+    let stats = client.query().brand("Motorola").run(all_device_ids(), |_| panic!("no device expected")).unwrap();
+    assert_eq!(QueryStats::default(), stats);
+    assert_eq!(lookups, calls.count("/v2/lookupdeviceid/json"));
+}
+
+#[test]
+fn query_run_test() {
+    let (mut client, calls) = create_mock_client();
+    client.set_cache_size(1000);
+    let mut ids = all_device_ids();
+    ids.push("not_a_device".to_string());
+    let mut found = vec![];
+    let stats = client.query()
+        .filter(CapabilityPredicate::equals("is_robot", "false"))
+        .concurrency(3)
+        .run(ids.clone(), |device| found.push(device))
+        .unwrap();
+    assert_eq!(QueryStats { candidates: 6, matched: 4, errors: 1 }, stats);
+    assert_eq!(4, found.len());
+    // the same capabilities are served by the cache
+    let lookups = calls.count("/v2/lookupdeviceid/json");
+    client.query().filter(CapabilityPredicate::equals("is_robot", "true")).collect(ids.clone()).unwrap();
+    assert_eq!(lookups + 1, calls.count("/v2/lookupdeviceid/json"));
+
+    let stats = client.query().concurrency(1).limit(2).run(ids.clone(), |_| {}).unwrap();
+    assert_eq!(2, stats.matched);
+    assert_eq!(2, stats.candidates);
+
+    let mut found = vec![];
+    let stats = client.query().limit(0).run(ids, |device| found.push(device)).unwrap();
+    assert_eq!(QueryStats::default(), stats);
+    assert!(found.is_empty());
+}
+
+#[test]
+fn query_errors_test() {
+    let (client, _) = create_mock_client();
+    let err = client.query().filter(CapabilityPredicate::at_least("resolution_widht", "1200")).collect(all_device_ids()).unwrap_err();
+    assert_eq!(WmErrorKind::InvalidCapability, err.kind);
+    assert!(err.msg.contains("did you mean resolution_width?"));
+    let err = client.query().filter(CapabilityPredicate::at_least("resolution_width", "wide")).collect(all_device_ids()).unwrap_err();
+    assert_eq!(WmErrorKind::InvalidValue, err.kind);
+    let err = client.query().filter(CapabilityPredicate::greater_than("is_tablet", "false")).collect(all_device_ids()).unwrap_err();
+    assert_eq!(WmErrorKind::InvalidValue, err.kind);
+    assert_eq!("Capability is_tablet can only be compared with Eq or Ne", err.msg);
+    let err = client.query().capabilities(vec!["model_nmae"]).collect(all_device_ids()).unwrap_err();
+    assert_eq!(WmErrorKind::InvalidCapability, err.kind);
+
+    // filtering by OS needs device_os
+    let (mut client, _) = create_mock_client();
+    client.static_caps.retain(|name| name != "device_os");
+    let err = client.query().os("android").collect(all_device_ids()).unwrap_err();
+    assert_eq!(WmErrorKind::InvalidCapability, err.kind);
+
+    let transport = MockTransport::new();
+    let client = WmClient::with_transport("http", "localhost", "8080", "", Box::new(transport.clone())).unwrap();
+    *transport.down.lock().unwrap() = true;
+    let err = client.query().collect(all_device_ids()).unwrap_err();
+    assert_eq!(WmErrorKind::Unreachable, err.kind);
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use common::*;
+use wmclient::*;
+
+fn export_snapshot() -> Vec<u8> {
+    let (client, _) = create_mock_client();
+    let mut exported = vec![];
+    client.export_enumerations(&mut exported).unwrap();
+    exported
+}
+
+#[test]
+fn export_enumerations_test() {
+    let snapshot = EnumerationSnapshot::read(export_snapshot().as_slice()).unwrap();
+    assert_eq!(1, snapshot.version);
+    assert_eq!("/usr/share/wurfl/wurfl.zip:for WURFL API 1.12.0", snapshot.info.wurfl_info);
+    assert_eq!("2021-09-01 10:00:00", snapshot.info.ltime());
+    assert_eq!(9, snapshot.make_models.len());
+    assert_eq!(None, snapshot.make_models[4].marketing_name);
+    assert_eq!(vec!["", "4.4", "8.0", "9", "10", "11"], snapshot.os_versions["Android"]);
+}
+
+#[test]
+fn import_enumerations_test() {
+    let exported = export_snapshot();
+    let transport = MockTransport::new();
+    let calls = transport.calls.clone();
+    let down = transport.down.clone();
+    let client = WmClient::with_transport("http", "localhost", "8080", "", Box::new(transport)).unwrap();
+    client.import_enumerations(exported.as_slice()).unwrap();
+
+    *down.lock().unwrap() = true;
+    assert_eq!(4, client.get_all_device_makes().unwrap().len());
+    assert_eq!(vec!["9.3", "10.2.1", "14.0"], client.get_all_versions_for_os("ios").unwrap());
+    assert_eq!(9, client.get_make_models().unwrap().len());
+    assert_eq!(0, calls.count("/v2/alldevices/json"));
+    assert_eq!(0, calls.count("/v2/alldeviceosversions/json"));
+
+    assert!(client.import_enumerations("{}".as_bytes()).is_err());
+    let newer = String::from_utf8(exported).unwrap().replacen("\"version\":1", "\"version\":2", 1);
+    let err = client.import_enumerations(newer.as_bytes()).unwrap_err();
+    assert!(err.msg.contains("Unsupported enumeration snapshot version 2"));
+}
+
+#[test]
+fn client_from_enumerations_with_server_down_test() {
+    let snapshot = EnumerationSnapshot::read(export_snapshot().as_slice()).unwrap();
+    let transport = MockTransport::new();
+    *transport.down.lock().unwrap() = true;
+    // without a snapshot the client cannot be created
+    assert!(WmClient::with_transport("http", "localhost", "8080", "", Box::new(transport.clone())).is_err());
+
+    let client = WmClient::with_enumerations("http", "localhost", "8080", "", Box::new(transport.clone()), snapshot).unwrap();
+    assert!(client.has_static_capability("brand_name"));
+    assert_eq!(4, client.important_headers.len());
+    assert_eq!("Galaxy S9", client.find_devices_for_make("samsung").unwrap().values[0].marketing_name);
+    assert!(client.lookup_useragent(SAMSUNG_UA.to_string()).is_err());
+
+    // once the server is back lookups work
+    *transport.down.lock().unwrap() = false;
+    assert!(client.lookup_useragent(SAMSUNG_UA.to_string()).is_ok());
+    assert_eq!(4, client.get_all_device_makes().unwrap().len());
+}
+
+#[test]
+fn imported_enumerations_follow_wurfl_updates_test() {
+    let exported = export_snapshot();
+    let transport = MockTransport::new();
+    let calls = transport.calls.clone();
+    let ltime = transport.ltime.clone();
+    let client = WmClient::with_transport("http", "localhost", "8080", "", Box::new(transport)).unwrap();
+    client.import_enumerations(exported.as_slice()).unwrap();
+    client.lookup_useragent(SAMSUNG_UA.to_string()).unwrap();
+    client.get_all_device_makes().unwrap();
+    assert_eq!(0, calls.count("/v2/alldevices/json"));
+
+    // the server loads new WURFL data: imported enumerations are dropped
+    *ltime.lock().unwrap() = "2021-10-01 10:00:00".to_string();
+    client.lookup_useragent(IPHONE_UA.to_string()).unwrap();
+    client.get_all_device_makes().unwrap();
+    assert_eq!(1, calls.count("/v2/alldevices/json"));
+}
+
+#[test]
+fn client_from_outdated_enumerations_test() {
+    let exported = export_snapshot();
+    let transport = MockTransport::new();
+    let calls = transport.calls.clone();
+
+    // the snapshot has the WURFL data loaded by the server: nothing is downloaded
+    let snapshot = EnumerationSnapshot::read(exported.as_slice()).unwrap();
+    let client = WmClient::with_enumerations("http", "localhost", "8080", "", Box::new(transport.clone()), snapshot).unwrap();
+    client.get_all_device_makes().unwrap();
+    assert_eq!(0, calls.count("/v2/alldevices/json"));
+
+    // the server has loaded new WURFL data: the snapshot is ignored
+    *transport.ltime.lock().unwrap() = "2021-10-01 10:00:00".to_string();
+    let snapshot = EnumerationSnapshot::read(exported.as_slice()).unwrap();
+    let client = WmClient::with_enumerations("http", "localhost", "8080", "", Box::new(transport.clone()), snapshot).unwrap();
+    assert_eq!("2021-10-01 10:00:00", client.get_info().unwrap().ltime());
+    client.get_all_device_makes().unwrap();
+    assert_eq!(1, calls.count("/v2/alldevices/json"));
+}
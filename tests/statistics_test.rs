@@ -0,0 +1,70 @@
+mod common;
+
+use std::collections::HashMap;
+
+use common::*;
+use wmclient::*;
+
+fn device(caps: Vec<(&str, &str)>) -> JSONDeviceData {
+    let capabilities: HashMap<String, String> = caps.into_iter().map(|(n, v)| (n.to_string(), v.to_string())).collect();
+    JSONDeviceData { capabilities, error: "".to_string(), mtime: 0, ltime: "".to_string() }
+}
+
+#[test]
+fn statistics_from_lookups_test() {
+    let (client, _) = create_mock_client();
+    let caps = CapabilitySet::new(&client, STATISTICS_CAPABILITIES.to_vec());
+    let mut stats = DeviceStatistics::new();
+    for ua in [SAMSUNG_UA, SAMSUNG_UA, IPHONE_UA, BOT_UA] {
+        stats.add(&client.lookup_useragent_with(&caps, ua.to_string()).unwrap());
+    }
+    assert_eq!(4, stats.total());
+
+    let report = stats.report();
+    assert_eq!(4, report.total);
+    assert_eq!(StatisticsEntry { value: "Smartphone".to_string(), count: 3, percentage: 75.0 }, report.form_factor[0]);
+    assert_eq!(StatisticsEntry { value: "Robot".to_string(), count: 1, percentage: 25.0 }, report.form_factor[1]);
+    assert_eq!(vec!["Samsung", "Apple", "Google"], report.brand_name.iter().map(|e| e.value.as_str()).collect::<Vec<&str>>());
+    assert_eq!(vec!["Android", "iOS", "unknown"], report.device_os.iter().map(|e| e.value.as_str()).collect::<Vec<&str>>());
+    assert_eq!(vec!["Android 10.0", "iOS 10.2.1", "unknown unknown"], report.device_os_version.iter().map(|e| e.value.as_str()).collect::<Vec<&str>>());
+    assert_eq!(vec![("human", 3), ("bot", 1)], report.traffic.iter().map(|e| (e.value.as_str(), e.count)).collect::<Vec<(&str, u64)>>());
+}
+
+#[test]
+fn statistics_missing_capabilities_and_merge_test() {
+    let mut stats = DeviceStatistics::new();
+    stats.add(&device(vec![("brand_name", "Apple"), ("form_factor", "Tablet"), ("device_os", "iPadOS"), ("is_robot", "false")]));
+    let mut other = DeviceStatistics::new();
+    other.add(&device(vec![("brand_name", "Apple"), ("form_factor", "")]));
+    stats.merge(&other);
+
+    let report = stats.report();
+    assert_eq!(2, report.total);
+    assert_eq!(vec![("Apple", 2)], report.brand_name.iter().map(|e| (e.value.as_str(), e.count)).collect::<Vec<(&str, u64)>>());
+    assert_eq!(vec!["Tablet", "unknown"], report.form_factor.iter().map(|e| e.value.as_str()).collect::<Vec<&str>>());
+    assert_eq!(vec!["iPadOS unknown", "unknown unknown"], report.device_os_version.iter().map(|e| e.value.as_str()).collect::<Vec<&str>>());
+    assert_eq!(vec![("human", 1), ("unknown", 1)], report.traffic.iter().map(|e| (e.value.as_str(), e.count)).collect::<Vec<(&str, u64)>>());
+
+    // empty statistics report no entries
+    assert!(DeviceStatistics::new().report().form_factor.is_empty());
+}
+
+#[test]
+fn statistics_export_test() {
+    let mut stats = DeviceStatistics::new();
+    stats.add(&device(vec![("brand_name", "Acme, Inc."), ("form_factor", "Smartphone"), ("device_os", "Android"), ("device_os_version", "9"), ("is_robot", "false")]));
+    stats.add(&device(vec![("brand_name", "Nokia"), ("form_factor", "Feature Phone"), ("device_os", "Nokia OS"), ("device_os_version", "1.0"), ("is_robot", "false")]));
+    stats.add(&device(vec![("brand_name", "Nokia"), ("form_factor", "Feature Phone"), ("device_os", "Nokia OS"), ("device_os_version", "1.0"), ("is_robot", "false")]));
+    let report = stats.report();
+
+    let csv = report.to_csv();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!("dimension,value,count,percentage", lines[0]);
+    assert_eq!("form_factor,Feature Phone,2,66.67", lines[1]);
+    assert!(lines.contains(&"brand_name,\"Acme, Inc.\",1,33.33"));
+    assert!(lines.contains(&"traffic,human,3,100.00"));
+    assert_eq!(1 + 2 + 2 + 2 + 2 + 1, lines.len());
+
+    let parsed: StatisticsReport = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+    assert_eq!(report, parsed);
+}
@@ -0,0 +1,89 @@
+#![cfg(feature = "tower")]
+mod common;
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::time::Duration;
+
+use common::*;
+use tower::{service_fn, Layer, ServiceExt};
+use wmclient::*;
+
+// Inner service that answers with the detected device, if any
+async fn echo_device(req: http::Request<()>) -> Result<Option<DetectedDevice>, Infallible> {
+    Ok(req.extensions().get::<DetectedDevice>().cloned())
+}
+
+fn request(user_agent: &str) -> http::Request<()> {
+    http::Request::builder().uri("/").header("User-Agent", user_agent).body(()).unwrap()
+}
+
+fn fallback_device() -> JSONDeviceData {
+    let mut capabilities = HashMap::new();
+    capabilities.insert("wurfl_id".to_string(), "generic".to_string());
+    capabilities.insert("form_factor".to_string(), "Desktop".to_string());
+    JSONDeviceData { capabilities, error: "".to_string(), mtime: 0, ltime: "".to_string() }
+}
+
+#[tokio::test]
+async fn layer_attaches_device_test() {
+    let (client, mock) = create_async_mock_client();
+    let service = WmDetectionLayer::new(client).layer(service_fn(echo_device));
+    let detected = service.oneshot(request(SAMSUNG_UA)).await.unwrap().unwrap();
+    assert!(!detected.is_fallback);
+    assert_eq!("samsung_sm_g960f_ver1", detected.device.capabilities.get("wurfl_id").unwrap().as_str());
+    assert_eq!(SAMSUNG_UA, mock.calls.last_post()["lookup_headers"]["User-Agent"].as_str().unwrap());
+}
+
+#[tokio::test]
+async fn layer_capabilities_test() {
+    let (client, mock) = create_async_mock_client();
+    let layer = WmDetectionLayer::new(client).capabilities(vec!["brand_name", "form_factor"]).unwrap();
+    let detected = layer.layer(service_fn(echo_device)).oneshot(request(IPHONE_UA)).await.unwrap().unwrap();
+    let mut names: Vec<&String> = detected.device.capabilities.keys().collect();
+    names.sort();
+    assert_eq!(vec!["brand_name", "form_factor", "wurfl_id"], names);
+    assert_eq!("Apple", detected.device.capabilities.get("brand_name").unwrap().as_str());
+    // capabilities are requested to the server
+    assert_eq!("form_factor", mock.calls.last_post()["requested_vcaps"][0].as_str().unwrap());
+
+    let (client, _) = create_async_mock_client();
+    let err = WmDetectionLayer::new(client).capabilities(vec!["not_a_cap"]).err().unwrap();
+    assert_eq!(WmErrorKind::InvalidCapability, err.kind);
+}
+
+#[tokio::test]
+async fn layer_fallback_on_error_test() {
+    let (client, mock) = create_async_mock_client();
+    *mock.down.lock().unwrap() = true;
+    let layer = WmDetectionLayer::new(client.clone());
+    // without a fallback device the request goes on with no device attached
+    let detected = layer.layer(service_fn(echo_device)).oneshot(request(SAMSUNG_UA)).await.unwrap();
+    assert!(detected.is_none());
+
+    let layer = WmDetectionLayer::new(client).fallback(fallback_device());
+    let detected = layer.layer(service_fn(echo_device)).oneshot(request(SAMSUNG_UA)).await.unwrap().unwrap();
+    assert!(detected.is_fallback);
+    assert_eq!("generic", detected.device.capabilities.get("wurfl_id").unwrap().as_str());
+}
+
+#[tokio::test]
+async fn layer_timeout_test() {
+    let (client, mock) = create_async_mock_client();
+    *mock.delay.lock().unwrap() = 500;
+    let layer = WmDetectionLayer::new(client).timeout(Duration::from_millis(50)).fallback(fallback_device());
+    let detected = layer.layer(service_fn(echo_device)).oneshot(request(SAMSUNG_UA)).await.unwrap().unwrap();
+    assert!(detected.is_fallback);
+}
+
+#[tokio::test]
+async fn async_client_lookups_test() {
+    let (client, _) = create_async_mock_client();
+    let device = client.lookup_useragent(SWITCH_UA.to_string()).await.unwrap();
+    assert_eq!("Nintendo", device.capabilities.get("brand_name").unwrap().as_str());
+    let device = client.lookup_device_id("apple_iphone_ver10_2_1".to_string()).await.unwrap();
+    assert_eq!("iOS", device.capabilities.get("device_os").unwrap().as_str());
+    let device = client.lookup_headers(vec![("User-Agent", BOT_UA)]).await.unwrap();
+    assert_eq!("google_bot", device.capabilities.get("wurfl_id").unwrap().as_str());
+    assert!(client.client().has_static_capability("brand_name"));
+}
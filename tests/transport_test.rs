@@ -0,0 +1,135 @@
+mod common;
+
+use std::collections::HashMap;
+use std::env;
+use std::sync::{Arc, Mutex};
+
+use common::*;
+use wmclient::*;
+
+#[test]
+fn create_with_transport_test() {
+    let (client, calls) = create_mock_client();
+    assert_eq!(4, client.important_headers.len());
+    assert!(client.has_static_capability("brand_name"));
+    assert!(client.has_virtual_capability("form_factor"));
+    // client creation downloads server info through the transport
+    let all_calls = calls.all();
+    assert_eq!(1, all_calls.len());
+    assert_eq!("GET", all_calls[0].method);
+    assert_eq!("http://localhost:8080/v2/getinfo/json", all_calls[0].url);
+}
+
+#[test]
+fn create_with_transport_and_base_uri_test() {
+    let transport = MockTransport::new();
+    let calls = transport.calls.clone();
+    let client_res = WmClient::with_transport("https", "wm.example.com", "443", "wm", Box::new(transport));
+    assert!(client_res.is_ok());
+    assert_eq!("https://wm.example.com:443/wm/v2/getinfo/json", calls.all()[0].url);
+}
+
+#[test]
+fn create_with_failing_transport_test() {
+    let transport = MockTransport::new();
+    *transport.down.lock().unwrap() = true;
+    let client_res = WmClient::with_transport("http", "localhost", "8080", "", Box::new(transport));
+    assert!(client_res.is_err());
+}
+
+#[test]
+fn lookup_through_transport_test() {
+    let (client, calls) = create_mock_client();
+    let device = client.lookup_useragent(SAMSUNG_UA.to_string()).unwrap();
+    assert_eq!("SM-G960F", device.capabilities.get("model_name").unwrap().as_str());
+
+    let request = calls.last_post();
+    assert_eq!(SAMSUNG_UA, request["lookup_headers"]["User-Agent"].as_str().unwrap());
+    assert!(calls.all().last().unwrap().url.ends_with("/v2/lookupuseragent/json"));
+
+    let device = client.lookup_device_id("nintendo_switch_ver1".to_string()).unwrap();
+    assert_eq!("Nintendo", device.capabilities.get("brand_name").unwrap().as_str());
+    assert_eq!("nintendo_switch_ver1", calls.last_post()["wurfl_id"].as_str().unwrap());
+
+    let mut headers = HashMap::new();
+    headers.insert("User-Agent", IPHONE_UA);
+    let device = client.lookup_headers(headers).unwrap();
+    assert_eq!("iOS", device.capabilities.get("device_os").unwrap().as_str());
+    assert!(calls.all().last().unwrap().url.ends_with("/v2/lookuprequest/json"));
+}
+
+#[test]
+fn lookup_with_transport_error_test() {
+    let transport = MockTransport::new();
+    let down = transport.down.clone();
+    let client = WmClient::with_transport("http", "localhost", "8080", "", Box::new(transport)).unwrap();
+    *down.lock().unwrap() = true;
+    let device_res = client.lookup_useragent(SAMSUNG_UA.to_string());
+    assert!(device_res.is_err());
+    assert!(device_res.err().unwrap().msg.contains("connection refused"));
+    assert!(client.get_all_device_makes().is_err());
+}
+
+// Transport that only keeps track of the timeouts it receives
+struct TimeoutTransport {
+    inner: MockTransport,
+    timeouts: Arc<Mutex<(u64, u64)>>,
+}
+
+impl Transport for TimeoutTransport {
+    fn get(&self, url: &str) -> Result<String, WmError> {
+        self.inner.get(url)
+    }
+
+    fn post(&self, url: &str, body: &str) -> Result<String, WmError> {
+        self.inner.post(url, body)
+    }
+
+    fn set_timeouts(&mut self, conn_timeout: u64, rw_timeout: u64) {
+        *self.timeouts.lock().unwrap() = (conn_timeout, rw_timeout);
+    }
+}
+
+#[test]
+fn set_http_timeout_is_forwarded_to_transport_test() {
+    let timeouts = Arc::new(Mutex::new((0, 0)));
+    let transport = TimeoutTransport { inner: MockTransport::new(), timeouts: timeouts.clone() };
+    let mut client = WmClient::with_transport("http", "localhost", "8080", "", Box::new(transport)).unwrap();
+    client.set_http_timeout(5000, 20000);
+    assert_eq!((5000, 20000), *timeouts.lock().unwrap());
+}
+
+#[test]
+fn record_and_replay_through_transports_test() {
+    let path = env::temp_dir().join("wmclient_record_replay_transport_test.jsonl");
+    let fixture_path = path.to_str().unwrap();
+
+    // record a session done against the mock server
+    let recording = RecordingTransport::create(Box::new(MockTransport::new()), fixture_path).unwrap();
+    let client = WmClient::with_transport("http", "localhost", "8080", "", Box::new(recording)).unwrap();
+    let recorded_device = client.lookup_useragent(SAMSUNG_UA.to_string()).unwrap();
+    let mut headers = HashMap::new();
+    headers.insert("User-Agent", IPHONE_UA);
+    headers.insert("Accept-Language", "en");
+    let recorded_h_device = client.lookup_headers(headers.clone()).unwrap();
+    let recorded_makes = client.get_all_device_makes().unwrap();
+
+    // then replay it: same results, without the mock server
+    let replay_client = WmClient::new_replaying(fixture_path).unwrap();
+    let device = replay_client.lookup_useragent(SAMSUNG_UA.to_string()).unwrap();
+    assert_eq!(recorded_device.capabilities, device.capabilities);
+    let h_device = replay_client.lookup_headers(headers).unwrap();
+    assert_eq!(recorded_h_device.capabilities, h_device.capabilities);
+    let mut makes = replay_client.get_all_device_makes().unwrap();
+    let mut expected_makes = recorded_makes.clone();
+    makes.sort();
+    expected_makes.sort();
+    assert_eq!(expected_makes, makes);
+    assert!(replay_client.lookup_useragent(BOT_UA.to_string()).is_err());
+}
+
+#[test]
+fn wm_client_user_agent_test() {
+    let (client, _) = create_mock_client();
+    assert_eq!(format!("rust-wmclient-api-{}", client.get_api_version()), wm_client_user_agent());
+}
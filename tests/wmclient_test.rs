@@ -1,3 +1,4 @@
+#![allow(clippy::len_zero, clippy::unnecessary_unwrap, clippy::needless_return, clippy::get_first)]
 use std::borrow::BorrowMut;
 use std::env;
 
@@ -96,7 +97,7 @@ fn test_has_virtual_capability() {
 fn test_lookup_useragent_ok() {
     let cl_res = create_test_client();
     assert!(cl_res.is_ok());
-    let mut client = cl_res.unwrap();
+    let client = cl_res.unwrap();
     let ua = "Mozilla/5.0 (Linux; Android 7.0; SAMSUNG SM-G950F Build/NRD90M) AppleWebKit/537.36 (KHTML, like Gecko) SamsungBrowser/5.2 Chrome/51.0.2704.106 Mobile Safari/537.36";
     let device_res = client.lookup_useragent(ua.to_string());
     assert!(device_res.is_ok());
@@ -133,7 +134,7 @@ fn test_multiple_lookup_useragent(){
 fn test_lookup_empty_useragent() {
     let cl_res = create_test_client();
     assert!(cl_res.is_ok());
-    let mut client = cl_res.unwrap();
+    let client = cl_res.unwrap();
     let device_res = client.lookup_useragent("".to_string());
     assert!(device_res.is_ok());
     let device = device_res.unwrap();
@@ -230,7 +231,7 @@ fn test_reset_cache_on_requested_caps_change() {
 fn test_lookup_headers_ok() {
     let client_res = create_test_client();
     assert!(client_res.is_ok());
-    let mut client = client_res.unwrap();
+    let client = client_res.unwrap();
     // Let's create test headers
     let mut headers: HashMap<String,String> = HashMap::new();
     headers.insert("X-Requested-With".to_string(),"json_client".to_string());
@@ -275,7 +276,7 @@ fn test_lookup_headers_with_specific_caps() {
 fn test_lookup_headers_with_mixed_case() {
     let client_res = create_test_client();
     assert!(client_res.is_ok());
-    let mut client = client_res.unwrap();
+    let client = client_res.unwrap();
     // Let's create test headers
     let mut headers: HashMap<String,String> = HashMap::new();
     headers.insert("X-Requested-With".to_string(),"json_client".to_string());
@@ -297,7 +298,7 @@ fn test_lookup_headers_with_mixed_case() {
 fn test_lookup_headers_with_empty_header_map() {
     let client_res = create_test_client();
     assert!(client_res.is_ok());
-    let mut client = client_res.unwrap();
+    let client = client_res.unwrap();
     // Passing an empty map should result in the creation of an empty request object, thus in a "generic" device detection...
     let headers: HashMap<String,String> = HashMap::new();
 
@@ -335,7 +336,7 @@ fn test_multiple_lookup_device_id(){
 fn test_lookup_wrong_device_id(){
     let client_res = create_test_client();
     assert!(client_res.is_ok());
-    let mut client = client_res.unwrap();
+    let client = client_res.unwrap();
     let result = client.lookup_device_id("doesnotexist".to_string());
     // wurfl is does not exist, method returns error
     assert!(result.is_err());